@@ -1,18 +1,66 @@
 use serde::de;
 
-use crate::de::{Deserializer, Error, Result};
+use crate::de::{Deserializer, Error, PathSegment, Result, StringLike};
 
-pub(crate) struct UnitVariantAccess<'a, 'b> {
-    de: &'a mut Deserializer<'b>,
+/// Returns the name of the enum variant in `v` without deserializing its content.
+///
+/// Accepts the representations produced for externally tagged enums, i.e. a string for unit
+/// variants and an object with exactly one key for all other variants. The whole input is
+/// validated, but the variant content is not interpreted. This allows routing a message before
+/// choosing the type to deserialize it into.
+///
+/// ```
+/// use serde_json_wasm::de::peek_variant;
+///
+/// assert_eq!(peek_variant(br#"{"transfer":{"amount":"5"}}"#), Ok("transfer"));
+/// assert_eq!(peek_variant(br#""pause""#), Ok("pause"));
+/// assert!(peek_variant(br#"{"transfer":{},"burn":{}}"#).is_err());
+/// ```
+pub fn peek_variant(v: &[u8]) -> Result<&str> {
+    let mut de = Deserializer::new(v);
+    let variant = match de.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
+        b'"' => {
+            de.eat_char();
+            borrowed_name(de.parse_string()?)?
+        }
+        b'{' => {
+            de.eat_char();
+            if de.parse_whitespace().ok_or(Error::EofWhileParsingObject)? == b'}' {
+                return Err(Error::ExpectedSingleKey);
+            }
+            let name = borrowed_name(de.parse_object_key()?)?;
+            de.parse_object_colon()?;
+            de.skip_value()?;
+            if de.parse_object_comma_or_end()? {
+                return Err(Error::ExpectedSingleKey);
+            }
+            name
+        }
+        _ => return Err(de.invalid_type("a string or an object")),
+    };
+    de.end()?;
+
+    Ok(variant)
+}
+
+fn borrowed_name(name: StringLike<'_>) -> Result<&str> {
+    match name {
+        StringLike::Borrowed(name) => Ok(name),
+        StringLike::Owned(_) => Err(Error::EscapedVariantName),
+    }
+}
+
+pub(crate) struct UnitVariantAccess<'a, 'b, 'm> {
+    de: &'a mut Deserializer<'b, 'm>,
 }
 
-impl<'a, 'b> UnitVariantAccess<'a, 'b> {
-    pub(crate) fn new(de: &'a mut Deserializer<'b>) -> Self {
+impl<'a, 'b, 'm> UnitVariantAccess<'a, 'b, 'm> {
+    pub(crate) fn new(de: &'a mut Deserializer<'b, 'm>) -> Self {
         UnitVariantAccess { de }
     }
 }
 
-impl<'a, 'de> de::EnumAccess<'de> for UnitVariantAccess<'a, 'de> {
+impl<'a, 'de, 'm> de::EnumAccess<'de> for UnitVariantAccess<'a, 'de, 'm> {
     type Error = Error;
     type Variant = Self;
 
@@ -25,7 +73,7 @@ impl<'a, 'de> de::EnumAccess<'de> for UnitVariantAccess<'a, 'de> {
     }
 }
 
-impl<'de, 'a> de::VariantAccess<'de> for UnitVariantAccess<'a, 'de> {
+impl<'de, 'a, 'm> de::VariantAccess<'de> for UnitVariantAccess<'a, 'de, 'm> {
     type Error = Error;
 
     fn unit_variant(self) -> Result<()> {
@@ -36,35 +84,54 @@ impl<'de, 'a> de::VariantAccess<'de> for UnitVariantAccess<'a, 'de> {
     where
         T: de::DeserializeSeed<'de>,
     {
-        Err(Error::InvalidType)
+        Err(Error::mismatch("a newtype variant", "unit variant"))
     }
 
     fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        Err(Error::InvalidType)
+        Err(Error::mismatch("a tuple variant", "unit variant"))
     }
 
     fn struct_variant<V>(self, _fields: &'static [&'static str], _visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        Err(Error::InvalidType)
+        Err(Error::mismatch("a struct variant", "unit variant"))
     }
 }
 
-pub(crate) struct StructVariantAccess<'a, 'b> {
-    de: &'a mut Deserializer<'b>,
+pub(crate) struct StructVariantAccess<'a, 'b, 'm> {
+    de: &'a mut Deserializer<'b, 'm>,
 }
 
-impl<'a, 'b> StructVariantAccess<'a, 'b> {
-    pub fn new(de: &'a mut Deserializer<'b>) -> Self {
+impl<'a, 'b, 'm> StructVariantAccess<'a, 'b, 'm> {
+    pub fn new(de: &'a mut Deserializer<'b, 'm>) -> Self {
         StructVariantAccess { de }
     }
+
+    /// Consumes the `}` after the content of the variant
+    fn end(self) -> Result<()> {
+        match self
+            .de
+            .parse_whitespace()
+            .ok_or(Error::EofWhileParsingValue)?
+        {
+            b'}' => {
+                self.de.eat_char();
+                self.de.depth -= 1;
+                if self.de.tracks_path() {
+                    self.de.path.pop();
+                }
+                Ok(())
+            }
+            _ => Err(Error::ExpectedSomeValue),
+        }
+    }
 }
 
-impl<'a, 'de> de::EnumAccess<'de> for StructVariantAccess<'a, 'de> {
+impl<'a, 'de, 'm> de::EnumAccess<'de> for StructVariantAccess<'a, 'de, 'm> {
     type Error = Error;
     type Variant = Self;
 
@@ -72,36 +139,55 @@ impl<'a, 'de> de::EnumAccess<'de> for StructVariantAccess<'a, 'de> {
     where
         V: de::DeserializeSeed<'de>,
     {
+        self.de.parse_whitespace();
+        let start = self.de.index;
         let val = seed.deserialize(&mut *self.de)?;
+        if self.de.tracks_path() {
+            self.de.path.push(PathSegment::Key(start..self.de.index));
+        }
         self.de.parse_object_colon()?;
         Ok((val, self))
     }
 }
 
-impl<'a, 'de> de::VariantAccess<'de> for StructVariantAccess<'a, 'de> {
+impl<'a, 'de, 'm> de::VariantAccess<'de> for StructVariantAccess<'a, 'de, 'm> {
     type Error = Error;
 
+    /// Accepts `{"variant":{}}` and `{"variant":null}` as unit variants
     fn unit_variant(self) -> Result<()> {
-        Err(Error::InvalidType)
-    }
-
-    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
-    where
-        T: de::DeserializeSeed<'de>,
-    {
-        let value = seed.deserialize(&mut *self.de)?;
-        // we remove trailing '}' to be consistent with struct_variant algorithm
         match self
             .de
             .parse_whitespace()
             .ok_or(Error::EofWhileParsingValue)?
         {
-            b'}' => {
+            b'n' => {
                 self.de.eat_char();
-                Ok(value)
+                self.de.parse_ident(b"ull")?;
             }
-            _ => Err(Error::ExpectedSomeValue),
+            b'{' => {
+                self.de.eat_char();
+                match self
+                    .de
+                    .parse_whitespace()
+                    .ok_or(Error::EofWhileParsingObject)?
+                {
+                    b'}' => self.de.eat_char(),
+                    _ => return Err(Error::mismatch("a unit variant", "object")),
+                }
+            }
+            _ => return Err(self.de.invalid_type("a unit variant")),
         }
+        self.end()
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        let value = seed.deserialize(&mut *self.de)?;
+        // we remove trailing '}' to be consistent with struct_variant algorithm
+        self.end()?;
+        Ok(value)
     }
 
     fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value>
@@ -109,17 +195,8 @@ impl<'a, 'de> de::VariantAccess<'de> for StructVariantAccess<'a, 'de> {
         V: de::Visitor<'de>,
     {
         let value = de::Deserializer::deserialize_tuple(&mut *self.de, len, visitor)?;
-        match self
-            .de
-            .parse_whitespace()
-            .ok_or(Error::EofWhileParsingValue)?
-        {
-            b'}' => {
-                self.de.eat_char();
-                Ok(value)
-            }
-            _ => Err(Error::ExpectedSomeValue),
-        }
+        self.end()?;
+        Ok(value)
     }
 
     fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
@@ -127,16 +204,64 @@ impl<'a, 'de> de::VariantAccess<'de> for StructVariantAccess<'a, 'de> {
         V: de::Visitor<'de>,
     {
         let value = de::Deserializer::deserialize_struct(&mut *self.de, "", fields, visitor)?;
-        match self
-            .de
-            .parse_whitespace()
-            .ok_or(Error::EofWhileParsingValue)?
-        {
-            b'}' => {
-                self.de.eat_char();
-                Ok(value)
-            }
-            _ => Err(Error::ExpectedSomeValue),
-        }
+        self.end()?;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::peek_variant;
+    use crate::de::Error;
+
+    #[test]
+    fn peek_variant_works() {
+        assert_eq!(
+            peek_variant(br#"{"transfer":{"amount":"5"}}"#),
+            Ok("transfer")
+        );
+        assert_eq!(
+            peek_variant(br#" { "transfer" : { "to": ["a", "b"] } } "#),
+            Ok("transfer")
+        );
+        assert_eq!(peek_variant(br#"{"add":[2,3]}"#), Ok("add"));
+        assert_eq!(peek_variant(br#"{"square":7}"#), Ok("square"));
+        assert_eq!(peek_variant(br#""exit""#), Ok("exit"));
+    }
+
+    #[test]
+    fn peek_variant_requires_exactly_one_key() {
+        assert_eq!(peek_variant(br#"{}"#), Err(Error::ExpectedSingleKey));
+        assert_eq!(
+            peek_variant(br#"{"a":1,"b":2}"#),
+            Err(Error::ExpectedSingleKey)
+        );
+        assert_eq!(peek_variant(br#"{"a":1,}"#), Err(Error::TrailingComma));
+    }
+
+    #[test]
+    fn peek_variant_validates_input() {
+        assert_eq!(
+            peek_variant(br#"[1]"#),
+            Err(Error::mismatch("a string or an object", "array"))
+        );
+        assert_eq!(
+            peek_variant(br#"5"#),
+            Err(Error::mismatch("a string or an object", "integer"))
+        );
+        assert_eq!(peek_variant(b""), Err(Error::EofWhileParsingValue));
+        assert_eq!(peek_variant(br#"{"a":tru}"#), Err(Error::ExpectedSomeIdent));
+        assert_eq!(
+            peek_variant(br#"{"a":1"#),
+            Err(Error::EofWhileParsingObject)
+        );
+        assert_eq!(
+            peek_variant(br#"{"a":1} {"b":2}"#),
+            Err(Error::TrailingCharacters)
+        );
+        assert_eq!(
+            peek_variant(br#"{"tr\u0061nsfer":{}}"#),
+            Err(Error::EscapedVariantName)
+        );
     }
 }
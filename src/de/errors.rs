@@ -1,4 +1,5 @@
 use serde::de;
+use std::borrow::Cow;
 use std::{error, fmt};
 
 /// Deserialization result
@@ -8,9 +9,35 @@ pub type Result<T> = core::result::Result<T, Error>;
 #[derive(Debug, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum Error {
+    /// An array has more elements than allowed.
+    ArrayTooLong,
+
+    /// An error together with the byte offset in the input where it was detected. Only produced
+    /// if [`Options::error_offset`](crate::de::Options::error_offset) is set.
+    AtOffset {
+        /// Byte offset in the input where the error was detected
+        offset: usize,
+        /// The underlying error
+        error: Box<Error>,
+    },
+
+    /// An error that occurred at a known position in the document. Only produced if
+    /// [`Options::error_path`](crate::de::Options::error_path) is set.
+    AtPath {
+        /// Path of object keys and array indices to the value that failed, like `$.stats.views[3]`
+        path: String,
+        /// Byte offset in the input where the error was detected
+        offset: usize,
+        /// The underlying error
+        error: Box<Error>,
+    },
+
     /// Control character (U+0000 to U+001F) found in string. Those must always be escaped.
     ControlCharacterInString,
 
+    /// Arrays and objects are nested deeper than allowed.
+    DepthLimitExceeded,
+
     /// EOF while parsing a list.
     EofWhileParsingList,
 
@@ -23,6 +50,9 @@ pub enum Error {
     /// EOF while parsing a JSON value.
     EofWhileParsingValue,
 
+    /// Enum variant name contains escape sequences and cannot be borrowed from the input.
+    EscapedVariantName,
+
     /// Expected this character to be a `':'`.
     ExpectedColon,
 
@@ -38,36 +68,98 @@ pub enum Error {
     /// Expected this character to be either a `','` or a `'}'`.
     ExpectedObjectCommaOrEnd,
 
+    /// Expected an object with exactly one key.
+    ExpectedSingleKey,
+
     /// Expected to parse either a `true`, `false`, or a `null`.
     ExpectedSomeIdent,
 
     /// Expected this character to start a JSON value.
     ExpectedSomeValue,
 
+    /// An error in one of the values of a [`StreamDeserializer`](crate::de::StreamDeserializer)
+    /// or [`ReaderStreamDeserializer`](crate::de::ReaderStreamDeserializer).
+    InDocument {
+        /// Byte offset in the input where the value that failed starts
+        offset: usize,
+        /// The underlying error
+        error: Box<Error>,
+    },
+
+    /// The input is longer than allowed.
+    InputTooLong,
+
     /// Invalid escape sequence
     InvalidEscape,
 
     /// Invalid number.
     InvalidNumber,
 
-    /// Invalid type
-    InvalidType,
+    /// Invalid type. Holds a description of the expected type, like `a u32`, and the kind of
+    /// token that was found instead, like `string`.
+    InvalidType {
+        /// What the target type accepts
+        expected: Cow<'static, str>,
+        /// The kind of JSON value in the input
+        found: &'static str,
+    },
 
     /// Invalid unicode code point.
     InvalidUnicodeCodePoint,
 
+    /// A value has the right type but is not accepted by the target type, like an unknown string
+    /// for a type that parses strings.
+    InvalidValue {
+        /// What the target type accepts
+        expected: Cow<'static, str>,
+        /// The kind of JSON value in the input
+        found: &'static str,
+    },
+
+    /// An integer does not fit into the target type. Holds the name of that type.
+    IntegerOverflow {
+        /// Name of the target integer type, like `u8`
+        ty: &'static str,
+    },
+
+    /// Reading the input failed. Holds the message of the underlying I/O error.
+    Io(String),
+
     /// Object key is not a string.
     KeyMustBeAString,
 
     /// Found a lone surrogate, which can exist in JSON but cannot be encoded to UTF-8
     LoneSurrogateFound,
 
+    /// A [`Meter`](crate::de::Meter) ran out of budget. Meters may return this or any other error.
+    MeterExhausted,
+
+    /// An object lacks a field that the target type requires. Holds the name of the field.
+    MissingField(&'static str),
+
+    /// An object has more members than allowed.
+    ObjectTooLong,
+
+    /// A string is longer than allowed.
+    StringTooLong,
+
     /// JSON has non-whitespace trailing characters after the value.
     TrailingCharacters,
 
     /// JSON has a comma after the last value in an array or map.
     TrailingComma,
 
+    /// An object has a field that the target type does not know. Holds the name of the field.
+    UnknownField(String),
+
+    /// An enum variant that the target type does not know
+    UnknownVariant {
+        /// Name of the variant in the input
+        variant: String,
+        /// Names of the variants of the target type
+        expected: &'static [&'static str],
+    },
+
     /// Custom error message from serde
     Custom(String),
 }
@@ -89,6 +181,257 @@ impl de::Error for Error {
     {
         Error::Custom(msg.to_string())
     }
+
+    fn invalid_type(unexp: de::Unexpected<'_>, exp: &dyn de::Expected) -> Self {
+        Error::InvalidType {
+            expected: Cow::Owned(exp.to_string()),
+            found: unexpected_kind(unexp),
+        }
+    }
+
+    fn invalid_value(unexp: de::Unexpected<'_>, exp: &dyn de::Expected) -> Self {
+        Error::InvalidValue {
+            expected: Cow::Owned(exp.to_string()),
+            found: unexpected_kind(unexp),
+        }
+    }
+
+    fn unknown_variant(variant: &str, expected: &'static [&'static str]) -> Self {
+        Error::UnknownVariant {
+            variant: variant.to_string(),
+            expected,
+        }
+    }
+
+    fn unknown_field(field: &str, _expected: &'static [&'static str]) -> Self {
+        Error::UnknownField(field.to_string())
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Error::MissingField(field)
+    }
+}
+
+impl Error {
+    /// Returns a number that identifies the kind of error and does not change between versions of
+    /// this crate, unlike the message. Use this to tell errors apart outside of Rust, e.g. in a
+    /// client of a contract.
+    ///
+    /// [`Error::AtOffset`], [`Error::AtPath`] and [`Error::InDocument`] return the code of the
+    /// error they wrap.
+    ///
+    /// | Code | Variant |
+    /// |------|---------|
+    /// | 100 | [`Custom`](Error::Custom) |
+    /// | 101 | [`ControlCharacterInString`](Error::ControlCharacterInString) |
+    /// | 102 | [`DepthLimitExceeded`](Error::DepthLimitExceeded) |
+    /// | 103 | [`EofWhileParsingList`](Error::EofWhileParsingList) |
+    /// | 104 | [`EofWhileParsingObject`](Error::EofWhileParsingObject) |
+    /// | 105 | [`EofWhileParsingString`](Error::EofWhileParsingString) |
+    /// | 106 | [`EofWhileParsingValue`](Error::EofWhileParsingValue) |
+    /// | 107 | [`EscapedVariantName`](Error::EscapedVariantName) |
+    /// | 108 | [`ExpectedColon`](Error::ExpectedColon) |
+    /// | 109 | [`ExpectedHighSurrogate`](Error::ExpectedHighSurrogate) |
+    /// | 110 | [`ExpectedListCommaOrEnd`](Error::ExpectedListCommaOrEnd) |
+    /// | 111 | [`ExpectedLowSurrogate`](Error::ExpectedLowSurrogate) |
+    /// | 112 | [`ExpectedObjectCommaOrEnd`](Error::ExpectedObjectCommaOrEnd) |
+    /// | 113 | [`ExpectedSingleKey`](Error::ExpectedSingleKey) |
+    /// | 114 | [`ExpectedSomeIdent`](Error::ExpectedSomeIdent) |
+    /// | 115 | [`ExpectedSomeValue`](Error::ExpectedSomeValue) |
+    /// | 116 | [`InputTooLong`](Error::InputTooLong) |
+    /// | 117 | [`InvalidEscape`](Error::InvalidEscape) |
+    /// | 118 | [`InvalidNumber`](Error::InvalidNumber) |
+    /// | 119 | [`InvalidType`](Error::InvalidType) |
+    /// | 120 | [`InvalidUnicodeCodePoint`](Error::InvalidUnicodeCodePoint) |
+    /// | 121 | [`InvalidValue`](Error::InvalidValue) |
+    /// | 122 | [`IntegerOverflow`](Error::IntegerOverflow) |
+    /// | 123 | [`Io`](Error::Io) |
+    /// | 124 | [`KeyMustBeAString`](Error::KeyMustBeAString) |
+    /// | 125 | [`LoneSurrogateFound`](Error::LoneSurrogateFound) |
+    /// | 126 | [`MissingField`](Error::MissingField) |
+    /// | 127 | [`TrailingCharacters`](Error::TrailingCharacters) |
+    /// | 128 | [`TrailingComma`](Error::TrailingComma) |
+    /// | 129 | [`UnknownField`](Error::UnknownField) |
+    /// | 130 | [`UnknownVariant`](Error::UnknownVariant) |
+    /// | 131 | [`ArrayTooLong`](Error::ArrayTooLong) |
+    /// | 132 | [`ObjectTooLong`](Error::ObjectTooLong) |
+    /// | 133 | [`StringTooLong`](Error::StringTooLong) |
+    /// | 134 | [`MeterExhausted`](Error::MeterExhausted) |
+    ///
+    /// Codes of [`ser::Error`](crate::ser::Error) start at 200.
+    pub fn code(&self) -> u16 {
+        match self {
+            Error::AtOffset { error, .. }
+            | Error::AtPath { error, .. }
+            | Error::InDocument { error, .. } => error.code(),
+            Error::Custom(_) => 100,
+            Error::ControlCharacterInString => 101,
+            Error::DepthLimitExceeded => 102,
+            Error::EofWhileParsingList => 103,
+            Error::EofWhileParsingObject => 104,
+            Error::EofWhileParsingString => 105,
+            Error::EofWhileParsingValue => 106,
+            Error::EscapedVariantName => 107,
+            Error::ExpectedColon => 108,
+            Error::ExpectedHighSurrogate => 109,
+            Error::ExpectedListCommaOrEnd => 110,
+            Error::ExpectedLowSurrogate => 111,
+            Error::ExpectedObjectCommaOrEnd => 112,
+            Error::ExpectedSingleKey => 113,
+            Error::ExpectedSomeIdent => 114,
+            Error::ExpectedSomeValue => 115,
+            Error::InputTooLong => 116,
+            Error::InvalidEscape => 117,
+            Error::InvalidNumber => 118,
+            Error::InvalidType { .. } => 119,
+            Error::InvalidUnicodeCodePoint => 120,
+            Error::InvalidValue { .. } => 121,
+            Error::IntegerOverflow { .. } => 122,
+            Error::Io(_) => 123,
+            Error::KeyMustBeAString => 124,
+            Error::LoneSurrogateFound => 125,
+            Error::MissingField(_) => 126,
+            Error::TrailingCharacters => 127,
+            Error::TrailingComma => 128,
+            Error::UnknownField(_) => 129,
+            Error::UnknownVariant { .. } => 130,
+            Error::ArrayTooLong => 131,
+            Error::ObjectTooLong => 132,
+            Error::StringTooLong => 133,
+            Error::MeterExhausted => 134,
+        }
+    }
+
+    /// Returns the byte offset in the input where the error was detected, if it is known. That is
+    /// the case if [`Options::error_offset`](crate::de::Options::error_offset) or
+    /// [`Options::error_path`](crate::de::Options::error_path) was set.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Error::AtOffset { offset, .. } | Error::AtPath { offset, .. } => Some(*offset),
+            Error::InDocument { error, .. } => error.offset(),
+            _ => None,
+        }
+    }
+
+    /// Returns the underlying error without the position it is wrapped in, e.g. to match on it
+    ///
+    /// ```
+    /// use serde_json_wasm::de::{from_str_with_options, Error, Options};
+    ///
+    /// let options = Options::new().error_offset(true);
+    /// let err = from_str_with_options::<bool>("truee", &options).unwrap_err();
+    /// assert_eq!(err.offset(), Some(4));
+    /// assert_eq!(err.into_inner(), Error::TrailingCharacters);
+    /// ```
+    pub fn into_inner(self) -> Error {
+        match self {
+            Error::AtOffset { error, .. }
+            | Error::AtPath { error, .. }
+            | Error::InDocument { error, .. } => error.into_inner(),
+            error => error,
+        }
+    }
+
+    /// Type mismatch where the expected type is known at compile time, which does not allocate
+    pub(crate) fn mismatch(expected: &'static str, found: &'static str) -> Self {
+        Error::InvalidType {
+            expected: Cow::Borrowed(expected),
+            found,
+        }
+    }
+
+    /// Formats the error like a compiler diagnostic, with the line of `input` that failed and a
+    /// caret under the position of the error.
+    ///
+    /// `input` must be the text that was deserialized. The position is only known if
+    /// [`Options::error_offset`](crate::de::Options::error_offset) or
+    /// [`Options::error_path`](crate::de::Options::error_path) was set, other errors are rendered
+    /// as their message alone.
+    ///
+    /// Only available with the `std` feature, which is enabled by default.
+    ///
+    /// ```
+    /// use serde_derive::Deserialize;
+    /// use serde_json_wasm::de::{from_str_with_options, Options};
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// struct Config {
+    ///     name: String,
+    ///     port: u16,
+    /// }
+    ///
+    /// let input = "{\n  \"name\": \"node\",\n  \"port\": \"80\"\n}";
+    /// let options = Options::new().error_path(true);
+    /// let err = from_str_with_options::<Config>(input, &options).unwrap_err();
+    /// let expected = [
+    ///     "error: Invalid type: expected a u16, found string at $.port",
+    ///     " --> line 3, column 11",
+    ///     "  |",
+    ///     "3 |   \"port\": \"80\"",
+    ///     "  |           ^",
+    /// ];
+    /// assert_eq!(err.render(input), expected.join("\n"));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn render(&self, input: &str) -> String {
+        let mut offset = match self.offset() {
+            Some(offset) => offset.min(input.len()),
+            None => return format!("error: {}", self),
+        };
+        while !input.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[offset..]
+            .find('\n')
+            .map_or(input.len(), |i| offset + i);
+        let line = input[line_start..line_end].trim_end_matches('\r');
+        let line_number = input[..line_start].matches('\n').count() + 1;
+        // Tabs are kept so that the caret lines up with the text above it
+        let padding: String = input[line_start..offset]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        let gutter = " ".repeat(line_number.to_string().len());
+        format!(
+            "error: {}\n{}--> line {}, column {}\n{} |\n{} | {}\n{} | {}^",
+            self,
+            gutter,
+            line_number,
+            input[line_start..offset].chars().count() + 1,
+            gutter,
+            line_number,
+            line,
+            gutter,
+            padding
+        )
+    }
+}
+
+/// Names the kind of value serde reports as unexpected, without its content
+fn unexpected_kind(unexp: de::Unexpected<'_>) -> &'static str {
+    use de::Unexpected::*;
+    match unexp {
+        Bool(_) => "boolean",
+        Unsigned(_) | Signed(_) => "integer",
+        Float(_) => "floating point number",
+        Char(_) => "character",
+        Str(_) => "string",
+        Bytes(_) => "bytes",
+        Unit => "null",
+        Option => "option",
+        NewtypeStruct => "newtype struct",
+        Seq => "array",
+        Map => "object",
+        Enum => "enum",
+        UnitVariant => "unit variant",
+        NewtypeVariant => "newtype variant",
+        TupleVariant => "tuple variant",
+        StructVariant => "struct variant",
+        Other(_) => "other value",
+    }
 }
 
 impl fmt::Display for Error {
@@ -97,17 +440,28 @@ impl fmt::Display for Error {
             f,
             "{}",
             match self {
+                Error::ArrayTooLong => "An array has more elements than allowed.",
+                Error::AtOffset { offset, error } => {
+                    let message = error.to_string();
+                    return write!(f, "{} at byte {}", message.trim_end_matches('.'), offset);
+                }
+                Error::AtPath { path, error, .. } => {
+                    let message = error.to_string();
+                    return write!(f, "{} at {}", message.trim_end_matches('.'), path);
+                }
                 Error::ControlCharacterInString => "Control character found in string.",
+                Error::DepthLimitExceeded => "Arrays and objects are nested deeper than allowed.",
                 Error::EofWhileParsingList => "EOF while parsing a list.",
                 Error::EofWhileParsingObject => "EOF while parsing an object.",
                 Error::EofWhileParsingString => "EOF while parsing a string.",
                 Error::EofWhileParsingValue => "EOF while parsing a JSON value.",
+                Error::EscapedVariantName => {
+                    "Enum variant name contains escape sequences and cannot be borrowed."
+                }
                 Error::ExpectedColon => "Expected this character to be a `':'`.",
                 Error::ExpectedHighSurrogate => "Expected a high surrogate (D800–DBFF).",
                 Error::ExpectedListCommaOrEnd => {
-                    "Expected this character to be either a `','` or\
-                     a \
-                     `']'`."
+                    "Expected this character to be either a `','` or a `']'`."
                 }
                 Error::ExpectedLowSurrogate => "Expected a low surrogate (DC00–DFFF).",
                 Error::ExpectedObjectCommaOrEnd => {
@@ -115,23 +469,56 @@ impl fmt::Display for Error {
                      or a \
                      `'}'`."
                 }
+                Error::ExpectedSingleKey => "Expected an object with exactly one key.",
                 Error::ExpectedSomeIdent => {
                     "Expected to parse either a `true`, `false`, or a \
                      `null`."
                 }
                 Error::ExpectedSomeValue => "Expected this character to start a JSON value.",
+                Error::InDocument { offset, error } => {
+                    let message = error.to_string();
+                    return write!(
+                        f,
+                        "{} in the value at byte {}",
+                        message.trim_end_matches('.'),
+                        offset
+                    );
+                }
+                Error::InputTooLong => "The input is longer than allowed.",
                 Error::InvalidEscape => "Invalid escape sequence.",
                 Error::InvalidNumber => "Invalid number.",
-                Error::InvalidType => "Invalid type",
+                Error::InvalidType { expected, found } => {
+                    return write!(f, "Invalid type: expected {}, found {}.", expected, found)
+                }
                 Error::InvalidUnicodeCodePoint => "Invalid unicode code point.",
+                Error::InvalidValue { expected, found } => {
+                    return write!(f, "Invalid value: expected {}, found {}.", expected, found)
+                }
+                Error::IntegerOverflow { ty } => {
+                    return write!(f, "Integer does not fit into `{}`.", ty)
+                }
+                Error::Io(msg) => return write!(f, "I/O error: {}", msg),
                 Error::KeyMustBeAString => "Object key is not a string.",
                 Error::LoneSurrogateFound => "Found a lone surrogate, which can exist in JSON but cannot be encoded to UTF-8.",
+                Error::MeterExhausted => "The meter ran out of budget.",
+                Error::MissingField(field) => return write!(f, "Missing field `{}`.", field),
+                Error::ObjectTooLong => "An object has more members than allowed.",
+                Error::StringTooLong => "A string is longer than allowed.",
                 Error::TrailingCharacters => {
                     "JSON has non-whitespace trailing characters after \
                      the \
                      value."
                 }
                 Error::TrailingComma => "JSON has a comma after the last value in an array or map.",
+                Error::UnknownField(field) => return write!(f, "Unknown field `{}`.", field),
+                Error::UnknownVariant { variant, expected } => {
+                    return write!(
+                        f,
+                        "Unknown variant `{}`, expected one of `{}`.",
+                        variant,
+                        expected.join("`, `")
+                    )
+                }
                 Error::Custom(msg) => msg,
             }
         )
@@ -1,14 +1,42 @@
-use crate::de::{Deserializer, Error};
+use std::ops::Range;
+
+use crate::de::{Deserializer, Error, PathSegment};
 use serde::de::{self, Visitor};
 
-pub struct MapAccess<'a, 'b> {
-    de: &'a mut Deserializer<'b>,
+pub struct MapAccess<'a, 'b, 'm> {
+    de: &'a mut Deserializer<'b, 'm>,
     first: bool,
+    /// Number of members so far
+    len: usize,
+    /// The fields of the struct that is deserialized, or `None` for a map
+    fields: Option<&'static [&'static str]>,
+    /// Position of the last key in the input, including the quotes
+    key: Range<usize>,
 }
 
-impl<'a, 'b> MapAccess<'a, 'b> {
-    pub(crate) fn new(de: &'a mut Deserializer<'b>) -> Self {
-        MapAccess { de, first: true }
+impl<'a, 'b, 'm> MapAccess<'a, 'b, 'm> {
+    pub(crate) fn new(
+        de: &'a mut Deserializer<'b, 'm>,
+        fields: Option<&'static [&'static str]>,
+    ) -> Self {
+        MapAccess {
+            de,
+            first: true,
+            len: 0,
+            fields,
+            key: 0..0,
+        }
+    }
+
+    /// Whether the last key is not one of the fields of the struct
+    fn is_unknown_key(&self) -> bool {
+        match self.fields {
+            Some(fields) if self.de.checks_fields() => {
+                let key = self.de.key_at(&self.key);
+                !fields.contains(&key.as_str())
+            }
+            _ => false,
+        }
     }
 }
 
@@ -17,7 +45,7 @@ macro_rules! deserialize_signed_key {
         let de = $self.de;
         match de.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
             b'"' => de.eat_char(),
-            _ => return Err(Error::InvalidType),
+            _ => return Err(de.invalid_type(concat!("an ", stringify!($ixx)))),
         };
 
         let result = match de.peek() {
@@ -25,14 +53,14 @@ macro_rules! deserialize_signed_key {
             // error[E0658]: or-patterns syntax is experimental
             Some(b'0'..=b'9') => super::deserialize_signed!(de, $visitor, $ixx, $visit_ixx),
             Some(b'-') => super::deserialize_signed!(de, $visitor, $ixx, $visit_ixx),
-            _ => return Err(Error::InvalidType),
+            _ => return Err(Error::mismatch(concat!("an ", stringify!($ixx)), "string")),
         };
         match de.peek() {
             Some(b'"') => {
                 de.eat_char();
                 result
             }
-            _ => Err(Error::InvalidType),
+            _ => Err(Error::mismatch(concat!("an ", stringify!($ixx)), "string")),
         }
     }};
 }
@@ -42,7 +70,7 @@ macro_rules! deserialize_unsigned_key {
         let de = $self.de;
         match de.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
             b'"' => de.eat_char(),
-            _ => return Err(Error::InvalidType),
+            _ => return Err(de.invalid_type(concat!("a ", stringify!($ixx)))),
         };
 
         let result = match de.peek() {
@@ -50,19 +78,19 @@ macro_rules! deserialize_unsigned_key {
             // error[E0658]: or-patterns syntax is experimental
             Some(b'0'..=b'9') => super::deserialize_unsigned!(de, $visitor, $ixx, $visit_ixx),
             Some(b'-') => super::deserialize_unsigned!(de, $visitor, $ixx, $visit_ixx),
-            _ => return Err(Error::InvalidType),
+            _ => return Err(Error::mismatch(concat!("a ", stringify!($ixx)), "string")),
         };
         match de.peek() {
             Some(b'"') => {
                 de.eat_char();
                 result
             }
-            _ => Err(Error::InvalidType),
+            _ => Err(Error::mismatch(concat!("a ", stringify!($ixx)), "string")),
         }
     }};
 }
 
-impl<'a, 'de> de::MapAccess<'de> for MapAccess<'a, 'de> {
+impl<'a, 'de, 'm> de::MapAccess<'de> for MapAccess<'a, 'de, 'm> {
     type Error = Error;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
@@ -90,7 +118,18 @@ impl<'a, 'de> de::MapAccess<'de> for MapAccess<'a, 'de> {
         };
 
         match peek.ok_or(Error::EofWhileParsingValue)? {
-            b'"' => seed.deserialize(MapKey { de: &mut *self.de }).map(Some),
+            b'"' => {
+                self.de.options.limits.check_object_member(self.len)?;
+                self.len += 1;
+                self.de.charge_scanned()?;
+                let start = self.de.index;
+                let key = seed.deserialize(MapKey { de: &mut *self.de })?;
+                self.key = start..self.de.index;
+                if self.de.tracks_path() {
+                    self.de.path.push(PathSegment::Key(self.key.clone()));
+                }
+                Ok(Some(key))
+            }
             b'}' => Err(Error::TrailingComma),
             _ => Err(Error::KeyMustBeAString),
         }
@@ -102,15 +141,29 @@ impl<'a, 'de> de::MapAccess<'de> for MapAccess<'a, 'de> {
     {
         self.de.parse_object_colon()?;
 
-        seed.deserialize(&mut *self.de)
+        // Tells `deserialize_ignored_any` that this value is skipped because its key is unknown
+        let unknown_value = if self.is_unknown_key() {
+            self.de.parse_whitespace();
+            Some(self.de.index)
+        } else {
+            None
+        };
+        let outer = std::mem::replace(&mut self.de.unknown_value, unknown_value);
+        let value = seed.deserialize(&mut *self.de);
+        self.de.unknown_value = outer;
+        let value = value?;
+        if self.de.tracks_path() {
+            self.de.path.pop();
+        }
+        Ok(value)
     }
 }
 
-struct MapKey<'a, 'b> {
-    de: &'a mut Deserializer<'b>,
+struct MapKey<'a, 'b, 'm> {
+    de: &'a mut Deserializer<'b, 'm>,
 }
 
-impl<'de, 'a> de::Deserializer<'de> for MapKey<'a, 'de> {
+impl<'de, 'a, 'm> de::Deserializer<'de> for MapKey<'a, 'de, 'm> {
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
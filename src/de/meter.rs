@@ -0,0 +1,74 @@
+//! Metering of the work done by the deserializer
+
+use crate::de::Result;
+
+/// Work done by the deserializer that a [`Meter`] is charged for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Charge {
+    /// Bytes of input that were scanned since the last charge
+    Bytes(usize),
+    /// Bytes that are about to be allocated to hold an unescaped string
+    Allocation(usize),
+    /// An array or object was opened. Holds the number of arrays and objects that are open now.
+    Nesting(usize),
+}
+
+/// Receives the charges for the work of a deserializer, e.g. to convert it into gas.
+///
+/// The input is charged in chunks at the start of array elements, object members and nested
+/// values, and before strings are unescaped, so the charges add up to the length of the input that
+/// was parsed. If [`charge`](Meter::charge) fails, deserialization stops at that charge point and
+/// returns the error. Scalars and skipped values are not charged byte by byte, so their bytes are
+/// only charged at the next element, member or nested value, or at the end of the input.
+///
+/// It is implemented for closures.
+///
+/// ```
+/// use serde_json_wasm::de::{from_slice_with_meter, Charge, Error, Options};
+///
+/// let mut gas = 100u64;
+/// let mut meter = |charge: Charge| {
+///     let cost = match charge {
+///         Charge::Bytes(n) => n as u64,
+///         Charge::Allocation(n) => 2 * n as u64,
+///         Charge::Nesting(depth) => 10 * depth as u64,
+///         _ => 0,
+///     };
+///     gas = gas.checked_sub(cost).ok_or(Error::MeterExhausted)?;
+///     Ok(())
+/// };
+/// let list: Vec<Vec<u8>> =
+///     from_slice_with_meter(b"[[1, 2], [3]]", &Options::default(), &mut meter).unwrap();
+/// assert_eq!(list, [vec![1, 2], vec![3]]);
+/// assert_eq!(gas, 100 - 13 - 10 - 20 - 20);
+///
+/// // Stops in the middle of the array
+/// let json = format!("[{}0]", "0,".repeat(1000));
+/// let mut charges = 0;
+/// let mut meter = |_: Charge| {
+///     charges += 1;
+///     if charges > 30 {
+///         return Err(Error::MeterExhausted);
+///     }
+///     Ok(())
+/// };
+/// assert_eq!(
+///     from_slice_with_meter::<Vec<u8>>(json.as_bytes(), &Options::default(), &mut meter),
+///     Err(Error::MeterExhausted)
+/// );
+/// assert_eq!(charges, 31);
+/// ```
+pub trait Meter {
+    /// Accounts for `charge`. An error aborts deserialization.
+    fn charge(&mut self, charge: Charge) -> Result<()>;
+}
+
+impl<F> Meter for F
+where
+    F: FnMut(Charge) -> Result<()>,
+{
+    fn charge(&mut self, charge: Charge) -> Result<()> {
+        self(charge)
+    }
+}
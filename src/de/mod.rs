@@ -3,23 +3,60 @@
 mod enum_;
 mod errors;
 mod map;
+mod meter;
+mod options;
+mod pointer;
 mod seq;
+mod stream;
+mod tokenizer;
 mod unescape;
+mod validate;
 
+pub use enum_::peek_variant;
 pub use errors::{Error, Result};
+pub use meter::{Charge, Meter};
+pub use options::Options;
+pub use pointer::{from_slice_at, pointer};
+pub use stream::{ReaderStreamDeserializer, StreamDeserializer};
+pub use tokenizer::{Event, Tokenizer};
+pub use validate::{validate, validate_with_limits, Limits};
 
 use serde::de::{self, Visitor};
 
 use self::enum_::{StructVariantAccess, UnitVariantAccess};
 use self::map::MapAccess;
 use self::seq::SeqAccess;
+use std::ops::Range;
 use std::str::from_utf8;
 
 /// Deserializer will parse serde-json-wasm flavored JSON into a
 /// serde-annotated struct
-pub struct Deserializer<'b> {
+pub struct Deserializer<'b, 'm> {
     slice: &'b [u8],
     index: usize,
+    options: Options,
+    /// Keys and indices leading to the value that is parsed, if `tracks_path` is true
+    path: Vec<PathSegment>,
+    /// Paths of the fields that were ignored, if they are collected
+    unknown_fields: Option<Vec<String>>,
+    /// Start of the value of a struct member whose key is not one of the fields, while it is
+    /// deserialized
+    unknown_value: Option<usize>,
+    /// Number of arrays and objects that are currently open
+    depth: usize,
+    /// Receives the charges for the work done, if metering is enabled
+    meter: Option<&'m mut dyn Meter>,
+    /// Index up to which the input was charged
+    charged: usize,
+}
+
+/// A step of the path from the root of the document to a value
+enum PathSegment {
+    /// The value of an object member. Holds the position of the key in the input, including the
+    /// quotes.
+    Key(Range<usize>),
+    /// An element of an array
+    Index(usize),
 }
 
 enum StringLike<'a> {
@@ -27,9 +64,137 @@ enum StringLike<'a> {
     Owned(String),
 }
 
-impl<'a> Deserializer<'a> {
-    fn new(slice: &'a [u8]) -> Deserializer<'_> {
-        Deserializer { slice, index: 0 }
+impl<'a, 'm> Deserializer<'a, 'm> {
+    fn new(slice: &'a [u8]) -> Deserializer<'a, 'm> {
+        Deserializer::with_options(slice, &Options::default())
+    }
+
+    fn with_options(slice: &'a [u8], options: &Options) -> Deserializer<'a, 'm> {
+        Deserializer {
+            slice,
+            index: 0,
+            options: options.clone(),
+            path: Vec::new(),
+            unknown_fields: None,
+            unknown_value: None,
+            depth: 0,
+            meter: None,
+            charged: 0,
+        }
+    }
+
+    /// Charges the meter, if any
+    fn charge(&mut self, charge: Charge) -> Result<()> {
+        match &mut self.meter {
+            Some(meter) => meter.charge(charge),
+            None => Ok(()),
+        }
+    }
+
+    /// Charges the meter, if any, for the input consumed since the last charge
+    fn charge_scanned(&mut self) -> Result<()> {
+        if self.meter.is_none() || self.index == self.charged {
+            return Ok(());
+        }
+        let scanned = self.index - self.charged;
+        self.charged = self.index;
+        self.charge(Charge::Bytes(scanned))
+    }
+
+    /// Called after the opening bracket of an array or object
+    fn enter(&mut self) -> Result<()> {
+        self.depth += 1;
+        match self.options.limits.max_depth {
+            Some(max) if self.depth > max => return Err(Error::DepthLimitExceeded),
+            _ => {}
+        }
+        self.charge_scanned()?;
+        self.charge(Charge::Nesting(self.depth))
+    }
+
+    /// Whether the path to the current value is needed
+    fn tracks_path(&self) -> bool {
+        self.options.deny_unknown_fields || self.options.error_path || self.unknown_fields.is_some()
+    }
+
+    /// Whether the members of structs are checked against their fields
+    fn checks_fields(&self) -> bool {
+        self.options.deny_unknown_fields || self.unknown_fields.is_some()
+    }
+
+    /// Adds the current path or offset to `error` if that is enabled. The path is still the one
+    /// of the value that failed, since it is only shortened after a value was deserialized.
+    fn locate(&self, error: Error) -> Error {
+        if self.options.error_path {
+            Error::AtPath {
+                path: self.path_string(),
+                offset: self.index,
+                error: Box::new(error),
+            }
+        } else if self.options.error_offset {
+            Error::AtOffset {
+                offset: self.index,
+                error: Box::new(error),
+            }
+        } else {
+            error
+        }
+    }
+
+    /// Returns the unescaped text of the key at `range`, which was parsed before
+    fn key_at(&self, range: &Range<usize>) -> String {
+        let mut de =
+            Deserializer::with_options(&self.slice[range.start + 1..range.end], &self.options);
+        match de.parse_string() {
+            Ok(StringLike::Borrowed(s)) => s.to_string(),
+            Ok(StringLike::Owned(s)) => s,
+            // Integer keys cannot contain escapes
+            Err(_) => {
+                String::from_utf8_lossy(&self.slice[range.start + 1..range.end - 1]).into_owned()
+            }
+        }
+    }
+
+    /// Returns the path to the current value, like `$.key[3]`
+    fn path_string(&self) -> String {
+        let mut path = String::from("$");
+        for segment in &self.path {
+            match segment {
+                PathSegment::Key(range) => {
+                    path.push('.');
+                    path.push_str(&self.key_at(range));
+                }
+                PathSegment::Index(index) => {
+                    path.push_str(&format!("[{}]", index));
+                }
+            }
+        }
+        path
+    }
+
+    /// Deserializes an object as a map, or as a struct with the given `fields`
+    fn deserialize_object<V>(
+        &mut self,
+        fields: Option<&'static [&'static str]>,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'a>,
+    {
+        let peek = self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
+
+        if peek == b'{' {
+            self.eat_char();
+            self.enter()?;
+
+            let ret = visitor.visit_map(MapAccess::new(self, fields))?;
+
+            self.end_map()?;
+
+            Ok(ret)
+        } else {
+            Err(self.invalid_type("an object"))
+        }
     }
 
     fn eat_char(&mut self) {
@@ -47,6 +212,7 @@ impl<'a> Deserializer<'a> {
         match self.parse_whitespace().ok_or(Error::EofWhileParsingList)? {
             b']' => {
                 self.eat_char();
+                self.depth -= 1;
                 Ok(())
             }
             b',' => {
@@ -67,6 +233,7 @@ impl<'a> Deserializer<'a> {
         {
             b'}' => {
                 self.eat_char();
+                self.depth -= 1;
                 Ok(())
             }
             b',' => Err(Error::TrailingComma),
@@ -109,9 +276,14 @@ impl<'a> Deserializer<'a> {
 
     fn parse_string(&mut self) -> Result<StringLike<'a>> {
         let start = self.index;
+        let max_len = self.options.limits.max_string_len.unwrap_or(usize::MAX);
         let mut contains_backslash = false;
         let mut escaped = false;
         loop {
+            // At the closing quote, this is the length of the string
+            if self.index - start > max_len {
+                return Err(Error::StringTooLong);
+            }
             match self.peek() {
                 Some(b'"') => {
                     if escaped {
@@ -120,10 +292,15 @@ impl<'a> Deserializer<'a> {
                     } else {
                         let end = self.index;
                         self.eat_char();
+                        self.charge_scanned()?;
                         return if contains_backslash {
-                            Ok(StringLike::Owned(unescape::unescape(
-                                &self.slice[start..end],
-                            )?))
+                            self.charge(Charge::Allocation(end - start))?;
+                            let source = &self.slice[start..end];
+                            Ok(StringLike::Owned(if self.options.lossy_surrogates {
+                                unescape::unescape_lossy(source)?
+                            } else {
+                                unescape::unescape(source)?
+                            }))
                         } else {
                             Ok(StringLike::Borrowed(
                                 from_utf8(&self.slice[start..end])
@@ -163,6 +340,172 @@ impl<'a> Deserializer<'a> {
     fn peek(&mut self) -> Option<u8> {
         self.slice.get(self.index).cloned()
     }
+
+    /// Names the kind of the next value for error messages
+    fn peek_kind(&mut self) -> &'static str {
+        match self.parse_whitespace() {
+            Some(b'"') => "string",
+            Some(b'[') => "array",
+            Some(b'{') => "object",
+            Some(b't') | Some(b'f') => "boolean",
+            Some(b'n') => "null",
+            Some(b'-') | Some(b'0'..=b'9') => "integer",
+            Some(_) => "unexpected character",
+            None => "end of input",
+        }
+    }
+
+    /// Type mismatch between `expected` and the next value
+    fn invalid_type(&mut self, expected: &'static str) -> Error {
+        Error::mismatch(expected, self.peek_kind())
+    }
+
+    /// Consumes one complete JSON value without handing it to a visitor.
+    ///
+    /// This checks the value with the same rules as the typed path: literals, integers, string
+    /// escapes and the structure of arrays and objects.
+    fn skip_value(&mut self) -> Result<()> {
+        match self.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
+            b'n' => {
+                self.eat_char();
+                self.parse_ident(b"ull")
+            }
+            b't' => {
+                self.eat_char();
+                self.parse_ident(b"rue")
+            }
+            b'f' => {
+                self.eat_char();
+                self.parse_ident(b"alse")
+            }
+            b'-' | b'0'..=b'9' => self.skip_integer(),
+            b'"' => {
+                self.eat_char();
+                self.parse_string().map(|_| ())
+            }
+            b'[' => {
+                self.eat_char();
+                self.enter()?;
+                self.skip_seq()?;
+                self.depth -= 1;
+                Ok(())
+            }
+            b'{' => {
+                self.eat_char();
+                self.enter()?;
+                self.skip_map()?;
+                self.depth -= 1;
+                Ok(())
+            }
+            _ => Err(Error::ExpectedSomeValue),
+        }
+    }
+
+    /// Consumes an integer of any length, i.e. `-?(0|[1-9][0-9]*)`
+    fn skip_integer(&mut self) -> Result<()> {
+        if self.peek() == Some(b'-') {
+            self.eat_char();
+        }
+        match self.peek() {
+            Some(b'0') => self.eat_char(),
+            Some(b'1'..=b'9') => {
+                self.eat_char();
+                while let Some(b'0'..=b'9') = self.peek() {
+                    self.eat_char();
+                }
+            }
+            _ => return Err(Error::InvalidNumber),
+        }
+        Ok(())
+    }
+
+    /// Consumes the elements of an array and its closing bracket
+    fn skip_seq(&mut self) -> Result<()> {
+        if self.parse_whitespace().ok_or(Error::EofWhileParsingList)? == b']' {
+            self.eat_char();
+            return Ok(());
+        }
+        let mut len = 0;
+        loop {
+            self.options.limits.check_array_element(len)?;
+            len += 1;
+            self.charge_scanned()?;
+            self.skip_value()?;
+            match self.parse_whitespace().ok_or(Error::EofWhileParsingList)? {
+                b',' => {
+                    self.eat_char();
+                    if self.parse_whitespace() == Some(b']') {
+                        return Err(Error::TrailingComma);
+                    }
+                }
+                b']' => {
+                    self.eat_char();
+                    return Ok(());
+                }
+                _ => return Err(Error::ExpectedListCommaOrEnd),
+            }
+        }
+    }
+
+    /// Consumes the members of an object and its closing brace
+    fn skip_map(&mut self) -> Result<()> {
+        if self
+            .parse_whitespace()
+            .ok_or(Error::EofWhileParsingObject)?
+            == b'}'
+        {
+            self.eat_char();
+            return Ok(());
+        }
+        let mut len = 0;
+        loop {
+            self.options.limits.check_object_member(len)?;
+            len += 1;
+            self.charge_scanned()?;
+            self.parse_object_key()?;
+            self.parse_object_colon()?;
+            self.skip_value()?;
+            if !self.parse_object_comma_or_end()? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Parses the key of an object member, including its quotes
+    fn parse_object_key(&mut self) -> Result<StringLike<'a>> {
+        match self
+            .parse_whitespace()
+            .ok_or(Error::EofWhileParsingObject)?
+        {
+            b'"' => {
+                self.eat_char();
+                self.parse_string()
+            }
+            _ => Err(Error::KeyMustBeAString),
+        }
+    }
+
+    /// Consumes the separator after an object member. Returns `true` if another member follows
+    /// and `false` if the closing brace was consumed.
+    fn parse_object_comma_or_end(&mut self) -> Result<bool> {
+        match self
+            .parse_whitespace()
+            .ok_or(Error::EofWhileParsingObject)?
+        {
+            b',' => {
+                self.eat_char();
+                if self.parse_whitespace() == Some(b'}') {
+                    return Err(Error::TrailingComma);
+                }
+                Ok(true)
+            }
+            b'}' => {
+                self.eat_char();
+                Ok(false)
+            }
+            _ => Err(Error::ExpectedObjectCommaOrEnd),
+        }
+    }
 }
 
 // NOTE(deserialize_*signed) we avoid parsing into u64 and then casting to a smaller integer, which
@@ -175,7 +518,12 @@ macro_rules! deserialize_unsigned {
             .ok_or(Error::EofWhileParsingValue)?;
 
         match peek {
-            b'-' => Err(Error::InvalidNumber),
+            b'-' => {
+                return Err(Error::mismatch(
+                    concat!("a ", stringify!($uxx)),
+                    "negative integer",
+                ))
+            }
             b'0' => {
                 $self.eat_char();
                 $visitor.$visit_uxx(0)
@@ -190,16 +538,20 @@ macro_rules! deserialize_unsigned {
                             $self.eat_char();
                             number = number
                                 .checked_mul(10)
-                                .ok_or(Error::InvalidNumber)?
+                                .ok_or(Error::IntegerOverflow {
+                                    ty: stringify!($uxx),
+                                })?
                                 .checked_add((c - b'0') as $uxx)
-                                .ok_or(Error::InvalidNumber)?;
+                                .ok_or(Error::IntegerOverflow {
+                                    ty: stringify!($uxx),
+                                })?;
                         }
                         _ => break,
                     }
                 }
                 $visitor.$visit_uxx(number)
             }
-            _ => Err(Error::InvalidType),
+            _ => Err($self.invalid_type(concat!("a ", stringify!($uxx)))),
         }
     }};
 }
@@ -233,22 +585,27 @@ macro_rules! deserialize_signed {
                             $self.eat_char();
                             number = number
                                 .checked_mul(10)
-                                .ok_or(Error::InvalidNumber)?
+                                .ok_or(Error::IntegerOverflow {
+                                    ty: stringify!($ixx),
+                                })?
                                 .checked_add((c - b'0') as $ixx * if signed { -1 } else { 1 })
-                                .ok_or(Error::InvalidNumber)?;
+                                .ok_or(Error::IntegerOverflow {
+                                    ty: stringify!($ixx),
+                                })?;
                         }
                         _ => break,
                     }
                 }
                 $visitor.$visit_ixx(number)
             }
-            _ => return Err(Error::InvalidType),
+            _ if signed => return Err(Error::InvalidNumber),
+            _ => return Err($self.invalid_type(concat!("an ", stringify!($ixx)))),
         }
     }};
 }
 pub(crate) use deserialize_signed;
 
-impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de, '_> {
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
@@ -287,6 +644,7 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
             }
             b'[' => {
                 self.eat_char();
+                self.enter()?;
                 let ret = visitor.visit_seq(SeqAccess::new(self))?;
 
                 self.end_seq()?;
@@ -295,7 +653,8 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
             }
             b'{' => {
                 self.eat_char();
-                let ret = visitor.visit_map(MapAccess::new(self))?;
+                self.enter()?;
+                let ret = visitor.visit_map(MapAccess::new(self, None))?;
 
                 self.end_map()?;
 
@@ -322,7 +681,7 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
                 self.parse_ident(b"alse")?;
                 visitor.visit_bool(false)
             }
-            _ => Err(Error::InvalidType),
+            _ => Err(self.invalid_type("a boolean")),
         }
     }
 
@@ -360,7 +719,7 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     {
         match self.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
             b'"' => self.eat_char(),
-            _ => return Err(Error::InvalidType),
+            _ => return Err(self.invalid_type("an i128 string")),
         };
 
         let result = match self.peek() {
@@ -368,14 +727,14 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
             // error[E0658]: or-patterns syntax is experimental
             Some(b'0'..=b'9') => deserialize_signed!(self, visitor, i128, visit_i128),
             Some(b'-') => deserialize_signed!(self, visitor, i128, visit_i128),
-            _ => return Err(Error::InvalidType),
+            _ => return Err(Error::mismatch("an i128 string", "string")),
         };
         match self.peek() {
             Some(b'"') => {
                 self.eat_char();
                 result
             }
-            _ => Err(Error::InvalidType),
+            _ => Err(Error::mismatch("an i128 string", "string")),
         }
     }
 
@@ -415,20 +774,20 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
             b'"' => {
                 self.eat_char();
             }
-            _ => return Err(Error::InvalidType),
+            _ => return Err(self.invalid_type("a u128 string")),
         };
 
         let result = match self.peek() {
-            Some(b'-') => return Err(Error::InvalidNumber),
+            Some(b'-') => return Err(Error::mismatch("a u128 string", "negative integer")),
             Some(b'0'..=b'9') => deserialize_unsigned!(self, visitor, u128, visit_u128),
-            _ => return Err(Error::InvalidType),
+            _ => return Err(Error::mismatch("a u128 string", "string")),
         };
         match self.peek() {
             Some(b'"') => {
                 self.eat_char();
                 result
             }
-            _ => Err(Error::InvalidType),
+            _ => Err(Error::mismatch("a u128 string", "string")),
         }
     }
 
@@ -475,7 +834,7 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
                     StringLike::Owned(string) => visitor.visit_string(string),
                 }
             }
-            _ => Err(Error::InvalidType),
+            _ => Err(self.invalid_type("a string")),
         }
     }
 
@@ -522,7 +881,7 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
             let ret = visitor.visit_unit()?;
             Ok(ret)
         } else {
-            Err(Error::InvalidType)
+            Err(self.invalid_type("null"))
         }
     }
 
@@ -535,10 +894,20 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     }
 
     /// Unsupported. We can’t parse newtypes because we don’t know the underlying type.
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if name == crate::raw::TOKEN {
+            // Hand out the exact text of the next value, see `RawValue`
+            self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
+            let start = self.index;
+            self.skip_value()?;
+            let raw = from_utf8(&self.slice[start..self.index])
+                .map_err(|_| Error::InvalidUnicodeCodePoint)?;
+            return visitor.visit_borrowed_str(raw);
+        }
+
         visitor.visit_newtype_struct(self)
     }
 
@@ -549,13 +918,14 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         match self.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
             b'[' => {
                 self.eat_char();
+                self.enter()?;
                 let ret = visitor.visit_seq(SeqAccess::new(self))?;
 
                 self.end_seq()?;
 
                 Ok(ret)
             }
-            _ => Err(Error::InvalidType),
+            _ => Err(self.invalid_type("an array")),
         }
     }
 
@@ -582,31 +952,19 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        let peek = self.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
-
-        if peek == b'{' {
-            self.eat_char();
-
-            let ret = visitor.visit_map(MapAccess::new(self))?;
-
-            self.end_map()?;
-
-            Ok(ret)
-        } else {
-            Err(Error::InvalidType)
-        }
+        self.deserialize_object(None, visitor)
     }
 
     fn deserialize_struct<V>(
         self,
         _name: &'static str,
-        _fields: &'static [&'static str],
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_map(visitor)
+        self.deserialize_object(Some(fields), visitor)
     }
 
     fn deserialize_enum<V>(
@@ -624,6 +982,7 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
             // if it is a struct enum
             b'{' => {
                 self.eat_char();
+                self.enter()?;
                 visitor.visit_enum(StructVariantAccess::new(self))
             }
             _ => Err(Error::ExpectedSomeIdent),
@@ -643,50 +1002,170 @@ impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        match self.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
-            b'"' => self.deserialize_str(visitor),
-            b'[' => self.deserialize_seq(visitor),
-            b'{' => self.deserialize_struct("ignored", &[], visitor),
-            b',' | b'}' | b']' => Err(Error::ExpectedSomeValue),
-            // If it’s something else then we chomp until we get to an end delimiter.
-            // This does technically allow for illegal JSON since we’re just ignoring
-            // characters rather than parsing them.
-            _ => loop {
-                match self.peek() {
-                    // The visitor is expected to be UnknownAny’s visitor, which
-                    // implements visit_unit to return its unit Ok result.
-                    Some(b',') | Some(b'}') | Some(b']') => break visitor.visit_unit(),
-                    Some(_) => self.eat_char(),
-                    None => break Err(Error::EofWhileParsingString),
+        // serde skips the value of a struct member whose key is not one of the fields with this.
+        // Other ignored values, like an `IgnoredAny` field, are not unknown.
+        self.parse_whitespace();
+        if self.unknown_value == Some(self.index) {
+            self.unknown_value = None;
+            if let Some(PathSegment::Key(key)) = self.path.last() {
+                if self.options.deny_unknown_fields {
+                    return Err(Error::UnknownField(self.key_at(key)));
                 }
-            },
+                let path = self.path_string();
+                if let Some(unknown_fields) = &mut self.unknown_fields {
+                    unknown_fields.push(path);
+                }
+            }
         }
+        // The visitor is expected to be the one of `IgnoredAny`, which accepts a unit
+        self.skip_value()?;
+        visitor.visit_unit()
     }
 }
 
 /// Deserializes an instance of type `T` from bytes of JSON text
-pub fn from_slice<T>(v: &[u8]) -> Result<T>
+pub fn from_slice<'a, T>(v: &'a [u8]) -> Result<T>
 where
-    T: de::DeserializeOwned,
+    T: de::Deserialize<'a>,
+{
+    from_slice_with_options(v, &Options::default())
+}
+
+/// Deserializes an instance of type `T` from bytes of JSON text, using the given options
+pub fn from_slice_with_options<'a, T>(v: &'a [u8], options: &Options) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    options.limits.check_input(v)?;
+    let mut de = Deserializer::with_options(v, options);
+    let value = de::Deserialize::deserialize(&mut de).map_err(|err| de.locate(err))?;
+    de.end().map_err(|err| de.locate(err))?;
+
+    Ok(value)
+}
+
+/// Deserializes an instance of type `T` from the JSON value at the start of `v`.
+///
+/// Returns the value together with the number of bytes it took up, including leading but not
+/// trailing whitespace. Whatever follows the value is left alone, so it can be used for JSON
+/// headers in front of a binary payload.
+///
+/// ```
+/// use serde_json_wasm::de::from_slice_partial;
+///
+/// let message = b"{\"len\":3}\n\x01\x02\x03";
+/// let (header, consumed): (std::collections::BTreeMap<String, u8>, usize) =
+///     from_slice_partial(message).unwrap();
+/// assert_eq!(header["len"], 3);
+/// assert_eq!(&message[consumed..], b"\n\x01\x02\x03");
+/// ```
+pub fn from_slice_partial<'a, T>(v: &'a [u8]) -> Result<(T, usize)>
+where
+    T: de::Deserialize<'a>,
 {
     let mut de = Deserializer::new(v);
     let value = de::Deserialize::deserialize(&mut de)?;
-    de.end()?;
+
+    Ok((value, de.index))
+}
+
+/// Deserializes an instance of type `T` from bytes of JSON text and returns the paths of the object
+/// members that `T` ignored, like `$.items[2].note`.
+///
+/// Keys are written as they are, without quoting. Members inside an ignored value are not
+/// listed on their own.
+///
+/// ```
+/// use serde_derive::Deserialize;
+/// use serde_json_wasm::de::{from_slice_with_unknown_fields, Options};
+///
+/// #[derive(Deserialize)]
+/// struct Order {
+///     id: u32,
+///     items: Vec<Item>,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Item {
+///     id: u32,
+/// }
+///
+/// let json = br#"{"id": 1, "items": [{"id": 2, "note": {"a": 1}}], "extra": true}"#;
+/// let (_, unknown): (Order, _) =
+///     from_slice_with_unknown_fields(json, &Options::default()).unwrap();
+/// assert_eq!(unknown, ["$.items[0].note", "$.extra"]);
+/// ```
+pub fn from_slice_with_unknown_fields<'a, T>(
+    v: &'a [u8],
+    options: &Options,
+) -> Result<(T, Vec<String>)>
+where
+    T: de::Deserialize<'a>,
+{
+    options.limits.check_input(v)?;
+    let mut de = Deserializer::with_options(v, options);
+    de.unknown_fields = Some(Vec::new());
+    let value = de::Deserialize::deserialize(&mut de).map_err(|err| de.locate(err))?;
+    de.end().map_err(|err| de.locate(err))?;
+
+    Ok((value, de.unknown_fields.unwrap_or_default()))
+}
+
+/// Deserializes an instance of type `T` from bytes of JSON text and charges `meter` for the work
+/// done, see [`Meter`]
+pub fn from_slice_with_meter<'a, T>(
+    v: &'a [u8],
+    options: &Options,
+    meter: &mut dyn Meter,
+) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    options.limits.check_input(v)?;
+    let mut de = Deserializer::with_options(v, options);
+    de.meter = Some(meter);
+    let value = de::Deserialize::deserialize(&mut de).map_err(|err| de.locate(err))?;
+    de.end().map_err(|err| de.locate(err))?;
+    de.charge_scanned()?;
 
     Ok(value)
 }
 
 /// Deserializes an instance of type T from a string of JSON text
-pub fn from_str<T>(s: &str) -> Result<T>
+pub fn from_str<'a, T>(s: &'a str) -> Result<T>
 where
-    T: de::DeserializeOwned,
+    T: de::Deserialize<'a>,
 {
     from_slice(s.as_bytes())
 }
 
+/// Deserializes an instance of type `T` from a string of JSON text, using the given options
+pub fn from_str_with_options<'a, T>(s: &'a str, options: &Options) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    from_slice_with_options(s.as_bytes(), options)
+}
+
+/// Deserializes an instance of type `T` from a string of JSON text and charges `meter` for the work
+/// done, see [`Meter`]
+pub fn from_str_with_meter<'a, T>(s: &'a str, options: &Options, meter: &mut dyn Meter) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    from_slice_with_meter(s.as_bytes(), options, meter)
+}
+
+/// Checks that `v` holds exactly one well-formed JSON value
+pub(crate) fn check_value(v: &[u8]) -> Result<()> {
+    let mut de = Deserializer::new(v);
+    de.skip_value()?;
+    de.end()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::from_str;
+    use super::{from_str, Error};
     use serde_derive::{Deserialize, Serialize};
 
     #[derive(Debug, Deserialize, PartialEq)]
@@ -755,7 +1234,10 @@ mod tests {
 
     #[test]
     fn integer128() {
-        assert_eq!(from_str::<i128>(r#"0"#), Err(crate::de::Error::InvalidType));
+        assert_eq!(
+            from_str::<i128>(r#"0"#),
+            Err(Error::mismatch("an i128 string", "integer"))
+        );
         assert_eq!(from_str::<i128>(r#""0""#), Ok(0));
         assert_eq!(from_str::<i128>(r#""1""#), Ok(1));
         assert_eq!(from_str::<i128>(r#""-1""#), Ok(-1));
@@ -766,7 +1248,7 @@ mod tests {
         );
         assert_eq!(
             from_str::<i128>(r#""170141183460469231731687303715884105728""#),
-            Err(crate::de::Error::InvalidNumber)
+            Err(Error::IntegerOverflow { ty: "i128" })
         );
         // min i128
         assert_eq!(
@@ -775,15 +1257,18 @@ mod tests {
         );
         assert_eq!(
             from_str::<i128>(r#""-170141183460469231731687303715884105729""#),
-            Err(crate::de::Error::InvalidNumber)
+            Err(Error::IntegerOverflow { ty: "i128" })
         );
 
-        assert_eq!(from_str::<u128>(r#"0"#), Err(crate::de::Error::InvalidType));
+        assert_eq!(
+            from_str::<u128>(r#"0"#),
+            Err(Error::mismatch("a u128 string", "integer"))
+        );
         assert_eq!(from_str::<u128>(r#""0""#), Ok(0));
         assert_eq!(from_str::<u128>(r#""1""#), Ok(1));
         assert_eq!(
             from_str::<u128>(r#""-1""#),
-            Err(crate::de::Error::InvalidNumber)
+            Err(Error::mismatch("a u128 string", "negative integer"))
         );
         // max u128
         assert_eq!(
@@ -792,7 +1277,7 @@ mod tests {
         );
         assert_eq!(
             from_str::<u128>(r#""340282366920938463463374607431768211456""#),
-            Err(crate::de::Error::InvalidNumber)
+            Err(Error::IntegerOverflow { ty: "u128" })
         )
     }
 
@@ -873,6 +1358,585 @@ mod tests {
         assert_eq!(from_str(r#" "thing" "#), Ok(Type::Thing));
     }
 
+    #[test]
+    fn unknown_fields() {
+        use super::{from_slice_with_options, from_slice_with_unknown_fields, Options};
+        use std::collections::BTreeMap;
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Outer {
+            id: u32,
+            inner: Vec<Inner>,
+        }
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Inner {
+            name: String,
+        }
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        #[serde(deny_unknown_fields)]
+        struct Strict {
+            id: u32,
+        }
+
+        let known = br#"{"id": 1, "inner": [{"name": "a"}]}"#;
+        let unknown = br#"{"id": 1, "inner": [{"name": "a"}, {"name": "b", "n\u0061me2": [{"x": 1}]}], "extra": null}"#;
+
+        let deny = Options::new().deny_unknown_fields(true);
+        assert!(from_slice_with_options::<Outer>(known, &deny).is_ok());
+        assert_eq!(
+            from_slice_with_options::<Outer>(unknown, &deny),
+            Err(Error::UnknownField("name2".to_string()))
+        );
+        assert!(from_slice_with_options::<Outer>(unknown, &Options::default()).is_ok());
+        // Ignored elements of sequences are not fields
+        assert!(
+            from_slice_with_options::<(u8, serde::de::IgnoredAny)>(b"[1, {\"a\": 2}]", &deny)
+                .is_ok()
+        );
+
+        let (value, paths) =
+            from_slice_with_unknown_fields::<Outer>(unknown, &Options::default()).unwrap();
+        assert_eq!(value.inner[1].name, "b");
+        assert_eq!(paths, ["$.inner[1].name2", "$.extra"]);
+        let (_, paths) =
+            from_slice_with_unknown_fields::<Outer>(known, &Options::default()).unwrap();
+        assert!(paths.is_empty());
+
+        // `#[serde(deny_unknown_fields)]` reports the same error
+        assert_eq!(
+            from_str::<Strict>(r#"{"id": 1, "other": 2}"#),
+            Err(Error::UnknownField("other".to_string()))
+        );
+
+        // Fields and map values that are ignored on purpose are known
+        #[derive(Deserialize, Debug)]
+        struct Skipping {
+            #[allow(dead_code)]
+            legacy: serde::de::IgnoredAny,
+            extra: BTreeMap<String, serde::de::IgnoredAny>,
+        }
+
+        let json = br#"{"legacy": {"a": 1}, "extra": {"b": [2]}}"#;
+        let value = from_slice_with_options::<Skipping>(json, &deny).unwrap();
+        assert_eq!(value.extra.len(), 1);
+        let (_, paths) =
+            from_slice_with_unknown_fields::<Skipping>(json, &Options::default()).unwrap();
+        assert!(paths.is_empty());
+        let json = br#"{"legacy": 1, "extra": {}, "other": {"c": 3}}"#;
+        assert_eq!(
+            from_slice_with_options::<Skipping>(json, &deny).unwrap_err(),
+            Error::UnknownField("other".to_string())
+        );
+        let (_, paths) =
+            from_slice_with_unknown_fields::<Skipping>(json, &Options::default()).unwrap();
+        assert_eq!(paths, ["$.other"]);
+    }
+
+    #[test]
+    fn error_path() {
+        use super::{from_str_with_options, Options};
+        use std::collections::BTreeMap;
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Post {
+            stats: Stats,
+            tags: BTreeMap<String, Vec<u8>>,
+            action: Option<Action>,
+        }
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Stats {
+            views: Vec<u32>,
+        }
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        enum Action {
+            Move { to: u8 },
+        }
+
+        let options = Options::new().error_path(true);
+        let at = |path: &str, offset: usize, error: Error| Error::AtPath {
+            path: path.to_string(),
+            offset,
+            error: Box::new(error),
+        };
+
+        let json = r#"{"stats": {"views": [1, 2, 3, -4]}, "tags": {}, "action": null}"#;
+        assert_eq!(
+            from_str_with_options::<Post>(json, &options),
+            Err(at(
+                "$.stats.views[3]",
+                30,
+                Error::mismatch("a u32", "negative integer")
+            ))
+        );
+        // Without the option, the error is unchanged
+        assert_eq!(
+            from_str_with_options::<Post>(json, &Options::default()),
+            Err(Error::mismatch("a u32", "negative integer"))
+        );
+
+        let json = r#"{"stats": {"views": []}, "tags": {"a.b": [1], "c": [2, true]}}"#;
+        assert_eq!(
+            from_str_with_options::<Post>(json, &options),
+            Err(at("$.tags.c[1]", 55, Error::mismatch("a u8", "boolean")))
+        );
+
+        let json = r#"{"stats": {"views": []}, "tags": {}, "action": {"Move": {"to": "x"}}}"#;
+        assert_eq!(
+            from_str_with_options::<Post>(json, &options),
+            Err(at(
+                "$.action.Move.to",
+                63,
+                Error::mismatch("a u8", "string")
+            ))
+        );
+
+        let json = r#"{"stats": {"views": []}, "tags": {}, "action": {"Move": {"to": 1}}}"#;
+        assert!(from_str_with_options::<Post>(json, &options).is_ok());
+
+        let err = from_str_with_options::<Post>(r#"{"stats": {}}"#, &options).unwrap_err();
+        assert_eq!(err.to_string(), "Missing field `views` at $.stats");
+        assert_eq!(
+            from_str_with_options::<Stats>(r#"{"views": []} x"#, &options),
+            Err(at("$", 14, Error::TrailingCharacters))
+        );
+    }
+
+    #[test]
+    fn limits() {
+        use super::{from_slice_with_options, from_str_with_options, Limits, Options};
+        use crate::RawValue;
+        use serde::de::IgnoredAny;
+        use std::collections::BTreeMap;
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Batch {
+            ids: Vec<u32>,
+            tags: BTreeMap<String, String>,
+        }
+
+        let json = r#"{"ids": [1, 2, 3], "tags": {"a": "xyz", "b": "\u0041"}}"#;
+        let options = |limits: Limits| Options::new().limits(limits);
+        let parse = |limits: Limits| from_str_with_options::<Batch>(json, &options(limits));
+
+        let limits = Limits::new()
+            .max_len(json.len())
+            .max_depth(2)
+            .max_string_len(6)
+            .max_array_len(3)
+            .max_object_len(2);
+        assert!(parse(limits.clone()).is_ok());
+        assert_eq!(
+            parse(limits.clone().max_len(json.len() - 1)),
+            Err(Error::InputTooLong)
+        );
+        assert_eq!(
+            parse(limits.clone().max_depth(1)),
+            Err(Error::DepthLimitExceeded)
+        );
+        // The escaped string is 6 bytes long in the input
+        assert_eq!(
+            parse(limits.clone().max_string_len(5)),
+            Err(Error::StringTooLong)
+        );
+        assert_eq!(
+            parse(limits.clone().max_array_len(2)),
+            Err(Error::ArrayTooLong)
+        );
+        assert_eq!(parse(limits.max_object_len(1)), Err(Error::ObjectTooLong));
+
+        // Ignored and raw values are checked as well
+        let limits = Limits::new().max_array_len(2);
+        assert_eq!(
+            from_str_with_options::<BTreeMap<String, IgnoredAny>>(
+                r#"{"a": [1, 2, 3]}"#,
+                &options(limits.clone())
+            )
+            .unwrap_err(),
+            Error::ArrayTooLong
+        );
+        assert_eq!(
+            from_str_with_options::<&RawValue>("[[], [1, 2, 3]]", &options(limits)).unwrap_err(),
+            Error::ArrayTooLong
+        );
+        assert_eq!(
+            from_slice_with_options::<IgnoredAny>(b"[[[]]]", &options(Limits::new().max_depth(2)))
+                .unwrap_err(),
+            Error::DepthLimitExceeded
+        );
+
+        // Struct variants count towards the depth
+        #[derive(Deserialize, Debug, PartialEq)]
+        enum Msg {
+            Send { to: Vec<u8> },
+        }
+        let json = r#"{"Send": {"to": [1]}}"#;
+        let limits = Limits::new().max_depth(3);
+        assert!(from_str_with_options::<Msg>(json, &options(limits)).is_ok());
+        let limits = Limits::new().max_depth(2);
+        assert_eq!(
+            from_str_with_options::<Msg>(json, &options(limits)),
+            Err(Error::DepthLimitExceeded)
+        );
+    }
+
+    #[test]
+    fn meter() {
+        use super::{from_slice_with_meter, from_str_with_meter, Charge, Options};
+        use crate::RawValue;
+        use std::collections::BTreeMap;
+
+        #[derive(Deserialize)]
+        struct Post<'a> {
+            title: String,
+            #[serde(borrow)]
+            body: &'a RawValue,
+        }
+
+        let json = br#" {"title": "a\"b", "extra": [[1]], "body": {"x": [true]}} "#;
+        let mut charges = Vec::new();
+        let mut meter = |charge: Charge| {
+            charges.push(charge);
+            Ok(())
+        };
+        let post: Post<'_> = from_slice_with_meter(json, &Options::default(), &mut meter).unwrap();
+        assert_eq!(post.title, "a\"b");
+        assert_eq!(post.body.get(), r#"{"x": [true]}"#);
+
+        // Every byte is charged once
+        let bytes: usize = charges
+            .iter()
+            .map(|charge| match charge {
+                Charge::Bytes(n) => *n,
+                _ => 0,
+            })
+            .sum();
+        assert_eq!(bytes, json.len());
+        assert!(charges.contains(&Charge::Allocation(4)));
+        // The ignored and the raw values are nested as well
+        let nesting: Vec<_> = charges
+            .iter()
+            .filter(|charge| matches!(charge, Charge::Nesting(_)))
+            .collect();
+        assert_eq!(
+            nesting,
+            [
+                &Charge::Nesting(1),
+                &Charge::Nesting(2),
+                &Charge::Nesting(3),
+                &Charge::Nesting(2),
+                &Charge::Nesting(3),
+            ]
+        );
+
+        // An error stops deserialization at the charge that failed
+        let json = br#"{"a": [1, 2], "b": [3, 4], "c": [5, 6]}"#;
+        let mut charged = 0;
+        let mut meter = |charge: Charge| {
+            if let Charge::Nesting(_) = charge {
+                charged += 1;
+                if charged == 3 {
+                    return Err(Error::MeterExhausted);
+                }
+            }
+            Ok(())
+        };
+        let options = Options::new().error_path(true);
+        let err = from_slice_with_meter::<BTreeMap<String, Vec<u8>>>(json, &options, &mut meter)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            Error::AtPath {
+                path: "$.b".to_string(),
+                offset: 20,
+                error: Box::new(Error::MeterExhausted),
+            }
+        );
+        assert_eq!(charged, 3);
+
+        // The meter is released when the call returns, even if the value borrows from the input
+        let mut scanned = 0;
+        let mut meter = |charge: Charge| {
+            if let Charge::Bytes(n) = charge {
+                scanned += n;
+            }
+            Ok(())
+        };
+        let title: &str =
+            from_str_with_meter(r#" "borrowed" "#, &Options::default(), &mut meter).unwrap();
+        assert_eq!(scanned, 12);
+        assert_eq!(title, "borrowed");
+    }
+
+    #[test]
+    fn error_codes() {
+        use super::{from_str_with_options, Options};
+
+        assert_eq!(from_str::<bool>("").unwrap_err().code(), 106);
+        assert_eq!(from_str::<[u8; 2]>("[1 2]").unwrap_err().code(), 110);
+        assert_eq!(from_str::<u8>("256").unwrap_err().code(), 122);
+        assert_eq!(Error::Custom("x".to_string()).code(), 100);
+
+        // The code does not depend on the path being tracked
+        let options = Options::new().error_path(true);
+        let err = from_str_with_options::<Vec<u8>>("[1, true]", &options).unwrap_err();
+        assert!(matches!(err, Error::AtPath { .. }));
+        assert_eq!(err.code(), 119);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn render_error() {
+        use super::{from_str_with_options, Options};
+
+        #[derive(Deserialize, Debug)]
+        struct Config {
+            #[allow(dead_code)]
+            peers: Vec<String>,
+        }
+
+        let options = Options::new().error_path(true);
+        let input = "\n\n\n\n\n\n\n\n\n{\"peers\": [\"a\",\t7]}";
+        let err = from_str_with_options::<Config>(input, &options).unwrap_err();
+        let expected = [
+            "error: Invalid type: expected a string, found integer at $.peers[1]",
+            "  --> line 10, column 17",
+            "   |",
+            "10 | {\"peers\": [\"a\",\t7]}",
+            "   |                \t^",
+        ];
+        assert_eq!(err.render(input), expected.join("\n"));
+
+        // Custom errors from serde are located too
+        let input = "{\r\n}\r\n";
+        let err = from_str_with_options::<Config>(input, &options).unwrap_err();
+        let expected = [
+            "error: Missing field `peers` at $",
+            " --> line 2, column 1",
+            "  |",
+            "2 | }",
+            "  | ^",
+        ];
+        assert_eq!(err.render(input), expected.join("\n"));
+
+        // At the end of the input
+        let input = "{\"peers\": [";
+        let err = from_str_with_options::<Config>(input, &options).unwrap_err();
+        let expected = [
+            "error: EOF while parsing a list at $.peers",
+            " --> line 1, column 12",
+            "  |",
+            "1 | {\"peers\": [",
+            "  |            ^",
+        ];
+        assert_eq!(err.render(input), expected.join("\n"));
+
+        // With only the offset
+        let input = "{\"peers\": [\"a\", \"b\",]}";
+        let options = Options::new().error_offset(true);
+        let err = from_str_with_options::<Config>(input, &options).unwrap_err();
+        let expected = [
+            "error: JSON has a comma after the last value in an array or map at byte 20",
+            " --> line 1, column 21",
+            "  |",
+            "1 | {\"peers\": [\"a\", \"b\",]}",
+            "  |                     ^",
+        ];
+        assert_eq!(err.render(input), expected.join("\n"));
+
+        // Without a position there is no snippet
+        let err = from_str::<Config>(input).unwrap_err();
+        assert_eq!(
+            err.render(input),
+            "error: JSON has a comma after the last value in an array or map."
+        );
+    }
+
+    #[test]
+    fn structured_errors() {
+        use std::num::NonZeroU8;
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Transfer {
+            to: String,
+            amount: u8,
+            kind: Kind,
+        }
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        #[serde(rename_all = "lowercase")]
+        enum Kind {
+            Mint,
+            Burn,
+        }
+
+        let err = from_str::<u32>(r#""5""#).unwrap_err();
+        assert_eq!(err, Error::mismatch("a u32", "string"));
+        assert_eq!(
+            err.to_string(),
+            "Invalid type: expected a u32, found string."
+        );
+        assert_eq!(
+            from_str::<Transfer>(r#"{"to": "alice", "amount": 300, "kind": "mint"}"#),
+            Err(Error::IntegerOverflow { ty: "u8" })
+        );
+        assert_eq!(
+            from_str::<Transfer>(r#"{"to": "alice", "kind": "mint"}"#),
+            Err(Error::MissingField("amount"))
+        );
+        let err = from_str::<u8>("-1").unwrap_err();
+        assert_eq!(err, Error::mismatch("a u8", "negative integer"));
+        assert_eq!(
+            err.to_string(),
+            "Invalid type: expected a u8, found negative integer."
+        );
+        assert_eq!(
+            from_str::<std::collections::BTreeMap<u16, bool>>(r#"{"-1": true}"#),
+            Err(Error::mismatch("a u16", "negative integer"))
+        );
+        assert_eq!(
+            from_str::<std::collections::BTreeMap<i8, bool>>(r#"{"x": true}"#),
+            Err(Error::mismatch("an i8", "string"))
+        );
+
+        let err = from_str::<Transfer>(r#"{"to": "a", "amount": 1, "kind": "stake"}"#).unwrap_err();
+        assert_eq!(
+            err,
+            Error::UnknownVariant {
+                variant: "stake".to_string(),
+                expected: &["mint", "burn"],
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Unknown variant `stake`, expected one of `mint`, `burn`."
+        );
+
+        assert_eq!(
+            from_str::<(u8, bool)>("[1, 2]"),
+            Err(Error::mismatch("a boolean", "integer"))
+        );
+        // Raised by the visitor in serde
+        assert_eq!(
+            from_str::<NonZeroU8>("0"),
+            Err(Error::InvalidValue {
+                expected: "a nonzero u8".into(),
+                found: "integer",
+            })
+        );
+    }
+
+    #[test]
+    fn lossy_surrogates() {
+        use super::{from_slice_with_options, Options};
+        use std::collections::BTreeMap;
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Message {
+            text: String,
+        }
+
+        let json = br#"{"text": "truncated \ud83d"}"#;
+        assert_eq!(
+            from_slice_with_options::<Message>(json, &Options::default()),
+            Err(Error::LoneSurrogateFound)
+        );
+        let options = Options::new().lossy_surrogates(true);
+        assert_eq!(
+            from_slice_with_options::<Message>(json, &options),
+            Ok(Message {
+                text: "truncated \u{FFFD}".to_string()
+            })
+        );
+
+        // Keys are unescaped the same way
+        let map: BTreeMap<String, u8> =
+            from_slice_with_options(br#"{"\udc4fkey": 1}"#, &options).unwrap();
+        assert_eq!(map["\u{FFFD}key"], 1);
+    }
+
+    #[test]
+    fn enum_unit_variant_object_forms() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "lowercase")]
+        enum Msg {
+            Pause,
+            Send(u8),
+        }
+
+        assert_eq!(from_str(r#""pause""#), Ok(Msg::Pause));
+        assert_eq!(from_str(r#"{"pause":{}}"#), Ok(Msg::Pause));
+        assert_eq!(from_str(r#" { "pause" : { } } "#), Ok(Msg::Pause));
+        assert_eq!(from_str(r#"{"pause":null}"#), Ok(Msg::Pause));
+        assert_eq!(
+            from_str::<Vec<Msg>>(r#"[{"pause":{}},{"send":1},"pause"]"#),
+            Ok(vec![Msg::Pause, Msg::Send(1), Msg::Pause])
+        );
+
+        // errors
+        assert_eq!(
+            from_str::<Msg>(r#"{"pause":{"a":1}}"#),
+            Err(Error::mismatch("a unit variant", "object"))
+        );
+        assert_eq!(
+            from_str::<Msg>(r#"{"pause":[]}"#),
+            Err(Error::mismatch("a unit variant", "array"))
+        );
+        assert_eq!(
+            from_str::<Msg>(r#"{"pause":1}"#),
+            Err(Error::mismatch("a unit variant", "integer"))
+        );
+        assert_eq!(
+            from_str::<Msg>(r#"{"pause":nul}"#),
+            Err(Error::ExpectedSomeIdent)
+        );
+        assert_eq!(
+            from_str::<Msg>(r#"{"pause":{},"send":1}"#),
+            Err(Error::ExpectedSomeValue)
+        );
+        assert_eq!(
+            from_str::<Msg>(r#"{"send":{}}"#),
+            Err(Error::mismatch("a u8", "object"))
+        );
+        assert_eq!(
+            from_str::<Msg>(r#""send""#),
+            Err(Error::mismatch("a newtype variant", "unit variant"))
+        );
+    }
+
+    #[test]
+    fn from_slice_partial() {
+        use super::from_slice_partial;
+
+        assert_eq!(from_slice_partial(b"true"), Ok((true, 4)));
+        assert_eq!(from_slice_partial(b"  true  "), Ok((true, 6)));
+        assert_eq!(from_slice_partial(b"[1, 2]\x00\xff"), Ok((vec![1, 2], 6)));
+        assert_eq!(from_slice_partial(br#""hello" world"#), Ok(("hello", 7)));
+        assert_eq!(from_slice_partial::<u8>(b"12 34"), Ok((12, 2)));
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Header {
+            len: u32,
+        }
+        assert_eq!(
+            from_slice_partial(b"{\"len\": 2}\r\n\x00\x01"),
+            Ok((Header { len: 2 }, 10))
+        );
+
+        // errors
+        assert_eq!(
+            from_slice_partial::<bool>(b""),
+            Err(Error::EofWhileParsingValue)
+        );
+        assert_eq!(
+            from_slice_partial::<Vec<u8>>(b"[1, 2"),
+            Err(Error::EofWhileParsingList)
+        );
+    }
+
     #[test]
     fn string() {
         assert_eq!(from_str(r#" "hello" "#), Ok("hello".to_string()));
@@ -1080,9 +2144,10 @@ mod tests {
             Ok(Temperature { temperature: 20 })
         );
 
+        // Ignored values are checked like all others
         assert_eq!(
-            from_str(r#"{ "temperature": 20, "invalid": this-is-ignored }"#),
-            Ok(Temperature { temperature: 20 })
+            from_str::<Temperature>(r#"{ "temperature": 20, "invalid": this-is-ignored }"#),
+            Err(crate::de::Error::ExpectedSomeIdent)
         );
 
         assert_eq!(
@@ -0,0 +1,110 @@
+use crate::de::Limits;
+
+/// Options that change how the deserializers read JSON
+///
+/// The default options are the strict rules used by [`from_slice`](crate::de::from_slice).
+///
+/// ```
+/// use serde_json_wasm::de::{from_str_with_options, Options};
+///
+/// let options = Options::new().lossy_surrogates(true);
+/// let name: String = from_str_with_options(r#""ab\ud83d""#, &options).unwrap();
+/// assert_eq!(name, "ab\u{FFFD}");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Options {
+    pub(crate) lossy_surrogates: bool,
+    pub(crate) deny_unknown_fields: bool,
+    pub(crate) error_offset: bool,
+    pub(crate) error_path: bool,
+    pub(crate) limits: Limits,
+}
+
+impl Options {
+    /// Creates the default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether unpaired UTF-16 surrogates in `\u` escapes are replaced with U+FFFD
+    /// REPLACEMENT CHARACTER. By default they are an error.
+    pub fn lossy_surrogates(mut self, enabled: bool) -> Self {
+        self.lossy_surrogates = enabled;
+        self
+    }
+
+    /// Sets whether fields that the target type ignores are rejected with
+    /// [`Error::UnknownField`](crate::de::Error::UnknownField), as if every struct was annotated
+    /// with `#[serde(deny_unknown_fields)]`. Only the members of structs and struct variants are
+    /// checked, so maps of `serde::de::IgnoredAny` values and fields of type `IgnoredAny` are
+    /// accepted. Fields of a `#[serde(flatten)]` struct are not checked either.
+    pub fn deny_unknown_fields(mut self, enabled: bool) -> Self {
+        self.deny_unknown_fields = enabled;
+        self
+    }
+
+    /// Sets whether errors are wrapped in [`Error::AtOffset`](crate::de::Error::AtOffset), which
+    /// tells the byte offset in the input where they were detected, e.g. for
+    /// [`Error::render`](crate::de::Error::render). [`error_path`](Options::error_path) takes
+    /// precedence, as its errors hold the offset as well.
+    ///
+    /// ```
+    /// use serde_json_wasm::de::{from_str_with_options, Error, Options};
+    ///
+    /// let options = Options::new().error_offset(true);
+    /// let err = from_str_with_options::<Vec<u8>>("[1, 2,]", &options).unwrap_err();
+    /// assert_eq!(err.offset(), Some(6));
+    /// assert_eq!(err.into_inner(), Error::TrailingComma);
+    /// ```
+    pub fn error_offset(mut self, enabled: bool) -> Self {
+        self.error_offset = enabled;
+        self
+    }
+
+    /// Sets whether errors are wrapped in [`Error::AtPath`](crate::de::Error::AtPath), which
+    /// tells the path and byte offset of the value that failed. Keeping track of the path makes
+    /// deserialization a bit slower.
+    ///
+    /// ```
+    /// use serde_derive::Deserialize;
+    /// use serde_json_wasm::de::{from_str_with_options, Options};
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// struct Stats {
+    ///     views: Vec<u32>,
+    /// }
+    ///
+    /// let options = Options::new().error_path(true);
+    /// let err = from_str_with_options::<Stats>(r#"{"views": [1, 2, 3, "4"]}"#, &options);
+    /// assert_eq!(
+    ///     err.unwrap_err().to_string(),
+    ///     "Invalid type: expected a u32, found string at $.views[3]"
+    /// );
+    /// ```
+    pub fn error_path(mut self, enabled: bool) -> Self {
+        self.error_path = enabled;
+        self
+    }
+
+    /// Sets the limits on the size of the input and of the values in it. By default there are no
+    /// limits.
+    ///
+    /// ```
+    /// use serde_json_wasm::de::{from_slice_with_options, Error, Limits, Options};
+    ///
+    /// let options = Options::new().limits(Limits::new().max_array_len(3).max_string_len(8));
+    /// assert_eq!(from_slice_with_options(b"[1, 2, 3]", &options), Ok(vec![1, 2, 3]));
+    /// assert_eq!(
+    ///     from_slice_with_options::<Vec<u8>>(b"[1, 2, 3, 4]", &options),
+    ///     Err(Error::ArrayTooLong)
+    /// );
+    /// assert_eq!(
+    ///     from_slice_with_options::<String>(br#""much too long""#, &options),
+    ///     Err(Error::StringTooLong)
+    /// );
+    /// ```
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+}
@@ -0,0 +1,258 @@
+//! Lookups of single values by JSON Pointer ([RFC 6901](https://tools.ietf.org/html/rfc6901))
+
+use std::borrow::Cow;
+
+use serde::de;
+
+use crate::de::{Deserializer, Error, Result, StringLike};
+
+/// Looks up the value addressed by the JSON Pointer `pointer` and returns its raw JSON text.
+///
+/// The input is scanned without deserializing anything: sibling values are only checked for
+/// well-formedness and skipped. Returns `Ok(None)` if the pointer does not address a value in
+/// the document or is not a valid JSON Pointer.
+///
+/// Scanning stops at the end of the addressed value, so anything after it is not validated.
+///
+/// ```
+/// use serde_json_wasm::de::pointer;
+///
+/// let json = br#"{"msg":{"transfer":{"amount":"42","to":"alice"}}}"#;
+/// assert_eq!(pointer(json, "/msg/transfer/amount").unwrap(), Some(&br#""42""#[..]));
+/// assert_eq!(pointer(json, "/msg/burn").unwrap(), None);
+/// ```
+pub fn pointer<'a>(v: &'a [u8], pointer: &str) -> Result<Option<&'a [u8]>> {
+    let mut de = Deserializer::new(v);
+    if !seek(&mut de, pointer)? {
+        return Ok(None);
+    }
+
+    let start = de.index;
+    de.skip_value()?;
+    Ok(Some(&v[start..de.index]))
+}
+
+/// Deserializes only the value addressed by the JSON Pointer `pointer`.
+///
+/// Returns `Ok(None)` if the pointer does not address a value in the document. See [`pointer`]
+/// for how the rest of the document is treated.
+pub fn from_slice_at<'a, T>(v: &'a [u8], pointer: &str) -> Result<Option<T>>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut de = Deserializer::new(v);
+    if !seek(&mut de, pointer)? {
+        return Ok(None);
+    }
+
+    de::Deserialize::deserialize(&mut de).map(Some)
+}
+
+/// Moves the deserializer to the start of the value addressed by `pointer`.
+/// Returns `false` if there is no such value.
+fn seek(de: &mut Deserializer<'_, '_>, pointer: &str) -> Result<bool> {
+    if pointer.is_empty() {
+        de.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
+        return Ok(true);
+    }
+    if !pointer.starts_with('/') {
+        return Ok(false);
+    }
+
+    for token in pointer[1..].split('/') {
+        let found = match de.parse_whitespace().ok_or(Error::EofWhileParsingValue)? {
+            b'{' => {
+                de.eat_char();
+                seek_member(de, &unescape_token(token))?
+            }
+            b'[' => {
+                de.eat_char();
+                match parse_index(token) {
+                    Some(index) => seek_element(de, index)?,
+                    None => false,
+                }
+            }
+            _ => false,
+        };
+        if !found {
+            return Ok(false);
+        }
+    }
+
+    de.parse_whitespace().ok_or(Error::EofWhileParsingValue)?;
+    Ok(true)
+}
+
+/// Skips the members of an object until the one named `key`, which is left unconsumed
+fn seek_member(de: &mut Deserializer<'_, '_>, key: &str) -> Result<bool> {
+    if de.parse_whitespace().ok_or(Error::EofWhileParsingObject)? == b'}' {
+        return Ok(false);
+    }
+    loop {
+        let matches = match de.parse_object_key()? {
+            StringLike::Borrowed(s) => s == key,
+            StringLike::Owned(s) => s == key,
+        };
+        de.parse_object_colon()?;
+        if matches {
+            return Ok(true);
+        }
+        de.skip_value()?;
+        if !de.parse_object_comma_or_end()? {
+            return Ok(false);
+        }
+    }
+}
+
+/// Skips the elements of an array until the one at `index`, which is left unconsumed
+fn seek_element(de: &mut Deserializer<'_, '_>, index: usize) -> Result<bool> {
+    if de.parse_whitespace().ok_or(Error::EofWhileParsingList)? == b']' {
+        return Ok(false);
+    }
+    for _ in 0..index {
+        de.skip_value()?;
+        match de.parse_whitespace().ok_or(Error::EofWhileParsingList)? {
+            b',' => {
+                de.eat_char();
+                if de.parse_whitespace() == Some(b']') {
+                    return Err(Error::TrailingComma);
+                }
+            }
+            b']' => return Ok(false),
+            _ => return Err(Error::ExpectedListCommaOrEnd),
+        }
+    }
+    Ok(true)
+}
+
+/// Decodes `~1` to `/` and `~0` to `~` in a reference token
+fn unescape_token(token: &str) -> Cow<'_, str> {
+    if token.contains('~') {
+        Cow::Owned(token.replace("~1", "/").replace("~0", "~"))
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+/// Parses an array index token, which must not have leading zeros
+fn parse_index(token: &str) -> Option<usize> {
+    if token.is_empty()
+        || !token.bytes().all(|b| b.is_ascii_digit())
+        || (token.len() > 1 && token.starts_with('0'))
+    {
+        return None;
+    }
+    token.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_slice_at, pointer};
+    use crate::de::Error;
+    use serde_derive::Deserialize;
+
+    const DOC: &[u8] = br#"{
+        "msg": {
+            "transfer": {"amount": "1000", "recipient": "alice"},
+            "memo": null
+        },
+        "funds": [{"denom": "uatom", "amount": 5}, {"denom": "ujuno", "amount": 7}],
+        "a/b": 1,
+        "m~n": 2,
+        "": 3,
+        "esc\"aped": true
+    }"#;
+
+    fn p(path: &str) -> Option<&'static str> {
+        pointer(DOC, path)
+            .unwrap()
+            .map(|raw| std::str::from_utf8(raw).unwrap())
+    }
+
+    #[test]
+    fn pointer_works() {
+        assert_eq!(p(""), Some(std::str::from_utf8(DOC).unwrap().trim()));
+        assert_eq!(p("/msg/transfer/amount"), Some(r#""1000""#));
+        assert_eq!(
+            p("/msg/transfer"),
+            Some(r#"{"amount": "1000", "recipient": "alice"}"#)
+        );
+        assert_eq!(p("/msg/memo"), Some("null"));
+        assert_eq!(p("/funds/0/denom"), Some(r#""uatom""#));
+        assert_eq!(p("/funds/1/amount"), Some("7"));
+        assert_eq!(p("/a~1b"), Some("1"));
+        assert_eq!(p("/m~0n"), Some("2"));
+        assert_eq!(p("/"), Some("3"));
+        assert_eq!(p("/esc\"aped"), Some("true"));
+    }
+
+    #[test]
+    fn pointer_returns_none_for_missing_values() {
+        assert_eq!(p("/nope"), None);
+        assert_eq!(p("/msg/transfer/amount/deeper"), None);
+        assert_eq!(p("/funds/2"), None);
+        assert_eq!(p("/funds/01"), None);
+        assert_eq!(p("/funds/-"), None);
+        assert_eq!(p("/funds/denom"), None);
+        assert_eq!(p("msg"), None);
+    }
+
+    #[test]
+    fn pointer_validates_skipped_values() {
+        assert_eq!(
+            pointer(br#"{"a": tru, "b": 1}"#, "/b"),
+            Err(Error::ExpectedSomeIdent)
+        );
+        assert_eq!(
+            pointer(br#"{"a": 1.5, "b": 1}"#, "/b"),
+            Err(Error::ExpectedObjectCommaOrEnd)
+        );
+        assert_eq!(
+            pointer(br#"{"a": "\x", "b": 1}"#, "/b"),
+            Err(Error::InvalidEscape)
+        );
+        assert_eq!(
+            pointer(br#"{"a": [1, 2,], "b": 1}"#, "/b"),
+            Err(Error::TrailingComma)
+        );
+        assert_eq!(
+            pointer(br#"{"a": {"x" 1}, "b": 1}"#, "/b"),
+            Err(Error::ExpectedColon)
+        );
+        assert_eq!(pointer(br#"[1, 2"#, "/5"), Err(Error::EofWhileParsingList));
+        assert_eq!(pointer(br#"[-]"#, "/1"), Err(Error::InvalidNumber));
+    }
+
+    #[test]
+    fn from_slice_at_works() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Coin {
+            denom: String,
+            amount: u64,
+        }
+
+        assert_eq!(
+            from_slice_at::<String>(DOC, "/msg/transfer/recipient"),
+            Ok(Some("alice".to_string()))
+        );
+        assert_eq!(
+            from_slice_at::<Coin>(DOC, "/funds/1"),
+            Ok(Some(Coin {
+                denom: "ujuno".to_string(),
+                amount: 7
+            }))
+        );
+        assert_eq!(
+            from_slice_at::<Vec<Coin>>(DOC, "/funds")
+                .unwrap()
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(from_slice_at::<u8>(DOC, "/nope"), Ok(None));
+        assert_eq!(
+            from_slice_at::<u8>(DOC, "/msg/transfer/amount"),
+            Err(Error::mismatch("a u8", "string"))
+        );
+    }
+}
@@ -1,19 +1,25 @@
 use serde::de;
 
-use crate::de::{Deserializer, Error, Result};
+use crate::de::{Deserializer, Error, PathSegment, Result};
 
-pub(crate) struct SeqAccess<'a, 'b> {
+pub(crate) struct SeqAccess<'a, 'b, 'm> {
     first: bool,
-    de: &'a mut Deserializer<'b>,
+    de: &'a mut Deserializer<'b, 'm>,
+    /// Index of the next element
+    index: usize,
 }
 
-impl<'a, 'b> SeqAccess<'a, 'b> {
-    pub fn new(de: &'a mut Deserializer<'b>) -> Self {
-        SeqAccess { de, first: true }
+impl<'a, 'b, 'm> SeqAccess<'a, 'b, 'm> {
+    pub fn new(de: &'a mut Deserializer<'b, 'm>) -> Self {
+        SeqAccess {
+            de,
+            first: true,
+            index: 0,
+        }
     }
 }
 
-impl<'a, 'de> de::SeqAccess<'de> for SeqAccess<'a, 'de> {
+impl<'a, 'de, 'm> de::SeqAccess<'de> for SeqAccess<'a, 'de, 'm> {
     type Error = Error;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
@@ -45,7 +51,18 @@ impl<'a, 'de> de::SeqAccess<'de> for SeqAccess<'a, 'de> {
         if peek == b']' {
             Err(Error::TrailingComma)
         } else {
-            Ok(Some(seed.deserialize(&mut *self.de)?))
+            self.de.options.limits.check_array_element(self.index)?;
+            self.de.charge_scanned()?;
+            let tracks_path = self.de.tracks_path();
+            if tracks_path {
+                self.de.path.push(PathSegment::Index(self.index));
+            }
+            self.index += 1;
+            let value = seed.deserialize(&mut *self.de)?;
+            if tracks_path {
+                self.de.path.pop();
+            }
+            Ok(Some(value))
         }
     }
 }
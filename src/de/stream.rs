@@ -0,0 +1,460 @@
+//! Deserialization of a sequence of concatenated or newline-delimited JSON values
+
+use std::io;
+use std::marker::PhantomData;
+
+use serde::de;
+
+use crate::de::{Deserializer, Error, Result};
+
+/// Number of bytes requested from the reader at a time
+const READ_CHUNK: usize = 8 * 1024;
+
+/// Iterator over a sequence of JSON values in a slice, such as JSON Lines.
+///
+/// The values may be separated by whitespace. Objects, arrays and strings may also directly
+/// follow the previous value, as in `{}{}`. Each item holds the value and the byte offset at
+/// which it starts. Iteration stops at the end of the input or after the first error, which is
+/// wrapped in [`Error::InDocument`] with the offset of the value that failed.
+///
+/// ```
+/// use serde_json_wasm::de::StreamDeserializer;
+///
+/// let input = b"{\"id\":1}\n{\"id\":2}\n";
+/// let offsets: Vec<usize> = StreamDeserializer::<serde::de::IgnoredAny>::new(input)
+///     .map(|item| item.unwrap().1)
+///     .collect();
+/// assert_eq!(offsets, [0, 9]);
+/// ```
+pub struct StreamDeserializer<'de, T> {
+    de: Deserializer<'de, 'static>,
+    offset: usize,
+    failed: bool,
+    output: PhantomData<T>,
+}
+
+impl<'de, T> StreamDeserializer<'de, T>
+where
+    T: de::Deserialize<'de>,
+{
+    /// Creates a stream deserializer over the JSON values in `v`
+    pub fn new(v: &'de [u8]) -> Self {
+        StreamDeserializer {
+            de: Deserializer::new(v),
+            offset: 0,
+            failed: false,
+            output: PhantomData,
+        }
+    }
+
+    /// Returns the number of bytes consumed so far.
+    ///
+    /// After an error this is the offset at which the failing value starts.
+    pub fn byte_offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'de, T> Iterator for StreamDeserializer<'de, T>
+where
+    T: de::Deserialize<'de>,
+{
+    type Item = Result<(T, usize)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        if self.de.parse_whitespace().is_none() {
+            self.offset = self.de.index;
+            return None;
+        }
+        self.offset = self.de.index;
+
+        let result = de::Deserialize::deserialize(&mut self.de)
+            .and_then(|value| end_of_value(&mut self.de).map(|()| value));
+        match result {
+            Ok(value) => {
+                let offset = self.offset;
+                self.offset = self.de.index;
+                Some(Ok((value, offset)))
+            }
+            Err(err) => {
+                self.failed = true;
+                Some(Err(in_document(err, self.offset)))
+            }
+        }
+    }
+}
+
+/// Iterator over a sequence of JSON values read from an [`io::Read`], such as a JSON Lines file.
+///
+/// This works like [`StreamDeserializer`], but buffers the input as needed. Values cannot
+/// borrow from the input. Read errors are returned as [`Error::Io`] and end the iteration. Like all
+/// other errors they are wrapped in [`Error::InDocument`].
+pub struct ReaderStreamDeserializer<R, T> {
+    reader: R,
+    buf: Vec<u8>,
+    /// Start of the unconsumed part of `buf`
+    pos: usize,
+    /// Number of bytes dropped from the front of `buf`
+    discarded: usize,
+    offset: usize,
+    eof: bool,
+    failed: bool,
+    output: PhantomData<T>,
+}
+
+impl<R, T> ReaderStreamDeserializer<R, T>
+where
+    R: io::Read,
+    T: de::DeserializeOwned,
+{
+    /// Creates a stream deserializer over the JSON values read from `reader`
+    pub fn new(reader: R) -> Self {
+        ReaderStreamDeserializer {
+            reader,
+            buf: Vec::new(),
+            pos: 0,
+            discarded: 0,
+            offset: 0,
+            eof: false,
+            failed: false,
+            output: PhantomData,
+        }
+    }
+
+    /// Returns the number of bytes consumed so far.
+    ///
+    /// After an error this is the offset at which the failing value starts.
+    pub fn byte_offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Reads `len` more bytes into the buffer, or less and sets `eof` if the reader runs out
+    fn fill(&mut self, len: usize) -> Result<()> {
+        let start = self.buf.len();
+        let mut filled = start;
+        self.buf.resize(start + len, 0);
+        while filled < start + len {
+            match self.reader.read(&mut self.buf[filled..]) {
+                Ok(0) => {
+                    self.eof = true;
+                    break;
+                }
+                Ok(n) => filled += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => {
+                    self.buf.truncate(filled);
+                    return Err(Error::Io(err.to_string()));
+                }
+            }
+        }
+        self.buf.truncate(filled);
+        Ok(())
+    }
+
+    fn next_value(&mut self) -> Option<Result<(T, usize)>> {
+        self.buf.drain(..self.pos);
+        self.discarded += self.pos;
+        self.pos = 0;
+
+        // Find the end of the value first. A value that runs into the end of the buffer may
+        // continue in the next chunk, so it is scanned again after reading more. The buffer at
+        // least doubles each time, which keeps the total work linear in the length of the value.
+        let (start, end) = loop {
+            let mut de = Deserializer::new(&self.buf);
+            let scanned = match de.parse_whitespace() {
+                None => None,
+                Some(_) => {
+                    let start = de.index;
+                    let result = de.skip_value().and_then(|()| end_of_value(&mut de));
+                    Some((start, result))
+                }
+            };
+            let start = scanned.as_ref().map_or(de.index, |(start, _)| *start);
+            self.offset = self.discarded + start;
+
+            if de.index == self.buf.len() && !self.eof {
+                let len = READ_CHUNK.max(self.buf.len());
+                if let Err(err) = self.fill(len) {
+                    return Some(Err(in_document(err, self.offset)));
+                }
+                continue;
+            }
+            match scanned {
+                Some((start, Ok(()))) => break (start, de.index),
+                Some((_, Err(err))) => return Some(Err(in_document(err, self.offset))),
+                None => {
+                    self.pos = de.index;
+                    return None;
+                }
+            }
+        };
+
+        // Only the value itself is deserialized, once
+        let mut de = Deserializer::new(&self.buf[..end]);
+        de.index = start;
+        Some(match de::Deserialize::deserialize(&mut de) {
+            Ok(value) => {
+                self.pos = end;
+                let offset = self.offset;
+                self.offset = self.discarded + self.pos;
+                Ok((value, offset))
+            }
+            Err(err) => Err(in_document(err, self.offset)),
+        })
+    }
+}
+
+impl<R, T> Iterator for ReaderStreamDeserializer<R, T>
+where
+    R: io::Read,
+    T: de::DeserializeOwned,
+{
+    type Item = Result<(T, usize)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let item = self.next_value();
+        if let Some(Err(_)) = item {
+            self.failed = true;
+        }
+        item
+    }
+}
+
+/// Wraps an error in the value starting at `offset`
+fn in_document(error: Error, offset: usize) -> Error {
+    Error::InDocument {
+        offset,
+        error: Box::new(error),
+    }
+}
+
+/// Checks that the value just parsed is not directly followed by more characters of the same
+/// token, as in `1x` or `truefalse`
+fn end_of_value(de: &mut Deserializer<'_, '_>) -> Result<()> {
+    match de.peek() {
+        None | Some(b' ') | Some(b'\n') | Some(b'\t') | Some(b'\r') => Ok(()),
+        Some(b'{') | Some(b'[') | Some(b'"') => Ok(()),
+        Some(_) => Err(Error::TrailingCharacters),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{in_document, ReaderStreamDeserializer, StreamDeserializer};
+    use crate::de::Error;
+    use serde_derive::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Event {
+        id: u32,
+        kind: String,
+    }
+
+    const LINES: &[u8] = b"{\"id\":1,\"kind\":\"mint\"}\n{\"id\":2,\"kind\":\"burn\"}\n\n";
+
+    /// Hands out its data one byte per read to exercise refilling
+    struct Trickle<'a>(&'a [u8]);
+
+    impl io::Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.split_first() {
+                Some((first, rest)) if !buf.is_empty() => {
+                    buf[0] = *first;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    fn event(id: u32, kind: &str) -> Event {
+        Event {
+            id,
+            kind: kind.to_string(),
+        }
+    }
+
+    #[test]
+    fn stream_newline_delimited() {
+        let mut stream = StreamDeserializer::<Event>::new(LINES);
+        assert_eq!(stream.next(), Some(Ok((event(1, "mint"), 0))));
+        assert_eq!(stream.byte_offset(), 22);
+        assert_eq!(stream.next(), Some(Ok((event(2, "burn"), 23))));
+        assert_eq!(stream.next(), None);
+        assert_eq!(stream.byte_offset(), LINES.len());
+        assert_eq!(stream.next(), None);
+
+        assert_eq!(StreamDeserializer::<Event>::new(b"").next(), None);
+        assert_eq!(StreamDeserializer::<Event>::new(b" \n ").next(), None);
+    }
+
+    #[test]
+    fn stream_concatenated() {
+        let values: Vec<(Vec<u8>, usize)> = StreamDeserializer::new(b"[1][2,3] [] [4]")
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            values,
+            [(vec![1], 0), (vec![2, 3], 3), (vec![], 9), (vec![4], 12)]
+        );
+
+        let values: Vec<(u32, usize)> = StreamDeserializer::new(b"1 22\n333")
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(values, [(1, 0), (22, 2), (333, 5)]);
+
+        let values: Vec<(&str, usize)> = StreamDeserializer::new(br#""a""b""#)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(values, [("a", 0), ("b", 3)]);
+    }
+
+    #[test]
+    fn stream_ignored_values() {
+        use serde::de::IgnoredAny;
+
+        let input = b"1\n2\n\"three\" null [4] {\"5\":5}\n";
+        let offsets: Vec<usize> = StreamDeserializer::<IgnoredAny>::new(input)
+            .map(|item| item.map(|(_, offset)| offset))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(offsets, [0, 2, 4, 12, 17, 21]);
+        let offsets: Vec<usize> = ReaderStreamDeserializer::<_, IgnoredAny>::new(Trickle(input))
+            .map(|item| item.map(|(_, offset)| offset))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(offsets, [0, 2, 4, 12, 17, 21]);
+
+        // Ignored values are still checked
+        let mut stream = StreamDeserializer::<IgnoredAny>::new(b"1 nul");
+        assert!(stream.next().unwrap().is_ok());
+        let err = stream.next().unwrap().unwrap_err();
+        assert_eq!(err, in_document(Error::ExpectedSomeIdent, 2));
+    }
+
+    #[test]
+    fn stream_stops_at_first_error() {
+        let input =
+            b"{\"id\":1,\"kind\":\"mint\"}\n{\"id\":2,\"kind\":}\n{\"id\":3,\"kind\":\"burn\"}";
+        let mut stream = StreamDeserializer::<Event>::new(input);
+        assert!(stream.next().unwrap().is_ok());
+        let err = stream.next().unwrap().unwrap_err();
+        assert_eq!(
+            err,
+            in_document(Error::mismatch("a string", "unexpected character"), 23)
+        );
+        assert_eq!(
+            err.to_string(),
+            "Invalid type: expected a string, found unexpected character in the value at byte 23"
+        );
+        assert_eq!(err.code(), 119);
+        assert_eq!(stream.byte_offset(), 23);
+        assert_eq!(stream.next(), None);
+
+        let mut stream = StreamDeserializer::<u32>::new(b"1 2x 3");
+        assert_eq!(stream.next(), Some(Ok((1, 0))));
+        assert_eq!(
+            stream.next(),
+            Some(Err(in_document(Error::TrailingCharacters, 2)))
+        );
+        assert_eq!(stream.byte_offset(), 2);
+        assert_eq!(stream.next(), None);
+
+        let mut stream = StreamDeserializer::<bool>::new(b"true,false");
+        assert_eq!(
+            stream.next(),
+            Some(Err(in_document(Error::TrailingCharacters, 0)))
+        );
+    }
+
+    #[test]
+    fn reader_stream_works() {
+        let events: Vec<(Event, usize)> = ReaderStreamDeserializer::new(LINES)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(events, [(event(1, "mint"), 0), (event(2, "burn"), 23)]);
+
+        let mut stream = ReaderStreamDeserializer::<_, Event>::new(Trickle(LINES));
+        assert_eq!(stream.next(), Some(Ok((event(1, "mint"), 0))));
+        assert_eq!(stream.next(), Some(Ok((event(2, "burn"), 23))));
+        assert_eq!(stream.next(), None);
+        assert_eq!(stream.byte_offset(), LINES.len());
+
+        // Numbers are not cut off at the end of a read
+        let values: Vec<(u64, usize)> = ReaderStreamDeserializer::new(Trickle(b"12 345 6789"))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(values, [(12, 0), (345, 3), (6789, 7)]);
+
+        // Values longer than a single read
+        let long = format!("\"{}\" \"x\"", "a".repeat(20_000));
+        let values: Vec<(String, usize)> = ReaderStreamDeserializer::new(long.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(values[0].0.len(), 20_000);
+        assert_eq!(values[1], ("x".to_string(), 20_003));
+        let values: Vec<(String, usize)> = ReaderStreamDeserializer::new(Trickle(long.as_bytes()))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(values[0].0.len(), 20_000);
+        assert_eq!(values[1], ("x".to_string(), 20_003));
+    }
+
+    #[test]
+    fn reader_stream_errors() {
+        let input = b"{\"id\":1,\"kind\":\"mint\"}\n{\"id\":2,\"kind\":\"bu";
+        let mut stream = ReaderStreamDeserializer::<_, Event>::new(Trickle(input));
+        assert!(stream.next().unwrap().is_ok());
+        assert_eq!(
+            stream.next(),
+            Some(Err(in_document(Error::EofWhileParsingString, 23)))
+        );
+        assert_eq!(stream.byte_offset(), 23);
+        assert_eq!(stream.next(), None);
+
+        // Syntax and type errors in later values carry the offset of that value
+        let input = b"{\"id\":1,\"kind\":\"mint\"} [1 2]";
+        let mut stream = ReaderStreamDeserializer::<_, Event>::new(Trickle(input));
+        assert!(stream.next().unwrap().is_ok());
+        assert_eq!(
+            stream.next(),
+            Some(Err(in_document(Error::ExpectedListCommaOrEnd, 23)))
+        );
+        let input = b"{\"id\":1,\"kind\":\"mint\"}\n[1]";
+        let mut stream = ReaderStreamDeserializer::<_, Event>::new(Trickle(input));
+        assert!(stream.next().unwrap().is_ok());
+        assert_eq!(
+            stream.next(),
+            Some(Err(in_document(Error::mismatch("an object", "array"), 23)))
+        );
+
+        struct Broken;
+
+        impl io::Read for Broken {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "access denied",
+                ))
+            }
+        }
+
+        let mut stream = ReaderStreamDeserializer::<_, u32>::new(Broken);
+        let err = stream.next().unwrap().unwrap_err();
+        assert_eq!(err, in_document(Error::Io("access denied".to_string()), 0));
+        assert_eq!(
+            err.to_string(),
+            "I/O error: access denied in the value at byte 0"
+        );
+        assert_eq!(stream.next(), None);
+    }
+}
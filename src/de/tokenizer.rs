@@ -0,0 +1,375 @@
+//! Pull parser yielding the tokens of a JSON document
+
+use std::borrow::Cow;
+use std::ops::Range;
+use std::str::from_utf8;
+
+use crate::de::{Deserializer, Error, Limits, Options, Result, StringLike};
+
+/// A token of a JSON document, as produced by [`Tokenizer`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<'a> {
+    /// `{`
+    StartObject,
+    /// `}`
+    EndObject,
+    /// `[`
+    StartArray,
+    /// `]`
+    EndArray,
+    /// The key of an object member, unescaped. It is followed by the events of the value.
+    Key(Cow<'a, str>),
+    /// A string value, unescaped
+    String(Cow<'a, str>),
+    /// An integer, as written in the input. It can be of any length.
+    Integer(&'a str),
+    /// `true` or `false`
+    Bool(bool),
+    /// `null`
+    Null,
+}
+
+#[derive(Clone, Copy)]
+enum Container {
+    Array,
+    Object,
+}
+
+/// What the tokenizer accepts next
+#[derive(Clone, Copy)]
+enum Expect {
+    /// Any value
+    Value,
+    /// A value or the end of the array that was just opened
+    FirstElement,
+    /// A key or the end of the object that was just opened
+    FirstKey,
+    /// A comma or the end of the enclosing container, or the end of the input at the top level
+    Separator,
+    /// Nothing, after the end of the input or an error
+    Done,
+}
+
+/// Iterator over the tokens of a single JSON document.
+///
+/// The input is checked with the same rules as the deserializer: only integers are accepted as
+/// numbers, strings are unescaped the same way, and trailing commas or trailing characters after
+/// the document are errors. Each event comes with the byte range of its token in the input; the
+/// range of strings and keys includes the quotes. After the first error the iterator is done.
+///
+/// ```
+/// use serde_json_wasm::de::{Event, Tokenizer};
+///
+/// let events: Vec<Event> = Tokenizer::new(br#"{"amount": [10, null]}"#)
+///     .map(|item| item.map(|(event, _range)| event))
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(
+///     events,
+///     [
+///         Event::StartObject,
+///         Event::Key("amount".into()),
+///         Event::StartArray,
+///         Event::Integer("10"),
+///         Event::Null,
+///         Event::EndArray,
+///         Event::EndObject,
+///     ]
+/// );
+/// ```
+pub struct Tokenizer<'a> {
+    de: Deserializer<'a, 'static>,
+    stack: Vec<Container>,
+    expect: Expect,
+    /// Byte range of the last token
+    token: Range<usize>,
+}
+
+impl<'a> Tokenizer<'a> {
+    /// Creates a tokenizer over the JSON document in `v`
+    pub fn new(v: &'a [u8]) -> Self {
+        Tokenizer::with_limits(v, &Limits::default())
+    }
+
+    /// Creates a tokenizer that enforces the string length limit of `limits`
+    pub(crate) fn with_limits(v: &'a [u8], limits: &Limits) -> Self {
+        Tokenizer {
+            de: Deserializer::with_options(v, &Options::new().limits(limits.clone())),
+            stack: Vec::new(),
+            expect: Expect::Value,
+            token: 0..0,
+        }
+    }
+
+    /// Returns the number of arrays and objects that are currently open
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Returns the number of bytes consumed so far
+    pub fn byte_offset(&self) -> usize {
+        self.de.index
+    }
+
+    fn next_event(&mut self) -> Result<Option<Event<'a>>> {
+        match self.expect {
+            Expect::Done => Ok(None),
+            Expect::Value => self.parse_value().map(Some),
+            Expect::FirstElement => {
+                if self
+                    .de
+                    .parse_whitespace()
+                    .ok_or(Error::EofWhileParsingList)?
+                    == b']'
+                {
+                    return Ok(Some(self.close()));
+                }
+                self.parse_value().map(Some)
+            }
+            Expect::FirstKey => {
+                if self
+                    .de
+                    .parse_whitespace()
+                    .ok_or(Error::EofWhileParsingObject)?
+                    == b'}'
+                {
+                    return Ok(Some(self.close()));
+                }
+                self.parse_key().map(Some)
+            }
+            Expect::Separator => match self.stack.last() {
+                None => {
+                    self.expect = Expect::Done;
+                    self.de.end()?;
+                    Ok(None)
+                }
+                Some(Container::Array) => {
+                    match self
+                        .de
+                        .parse_whitespace()
+                        .ok_or(Error::EofWhileParsingList)?
+                    {
+                        b',' => {
+                            self.de.eat_char();
+                            if self.de.parse_whitespace() == Some(b']') {
+                                return Err(Error::TrailingComma);
+                            }
+                            self.parse_value().map(Some)
+                        }
+                        b']' => Ok(Some(self.close())),
+                        _ => Err(Error::ExpectedListCommaOrEnd),
+                    }
+                }
+                Some(Container::Object) => {
+                    if self.de.parse_object_comma_or_end()? {
+                        self.parse_key().map(Some)
+                    } else {
+                        self.token = self.de.index - 1..self.de.index;
+                        self.stack.pop();
+                        Ok(Some(Event::EndObject))
+                    }
+                }
+            },
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Event<'a>> {
+        let peek = self
+            .de
+            .parse_whitespace()
+            .ok_or(Error::EofWhileParsingValue)?;
+        let start = self.de.index;
+        let event = match peek {
+            b'{' => {
+                self.de.eat_char();
+                self.stack.push(Container::Object);
+                Event::StartObject
+            }
+            b'[' => {
+                self.de.eat_char();
+                self.stack.push(Container::Array);
+                Event::StartArray
+            }
+            b'"' => {
+                self.de.eat_char();
+                Event::String(cow(self.de.parse_string()?))
+            }
+            b'-' | b'0'..=b'9' => {
+                self.de.skip_integer()?;
+                // Integers are ASCII only
+                Event::Integer(from_utf8(&self.de.slice[start..self.de.index]).unwrap())
+            }
+            b't' => {
+                self.de.eat_char();
+                self.de.parse_ident(b"rue")?;
+                Event::Bool(true)
+            }
+            b'f' => {
+                self.de.eat_char();
+                self.de.parse_ident(b"alse")?;
+                Event::Bool(false)
+            }
+            b'n' => {
+                self.de.eat_char();
+                self.de.parse_ident(b"ull")?;
+                Event::Null
+            }
+            _ => return Err(Error::ExpectedSomeValue),
+        };
+        self.expect = match event {
+            Event::StartObject => Expect::FirstKey,
+            Event::StartArray => Expect::FirstElement,
+            _ => Expect::Separator,
+        };
+        self.token = start..self.de.index;
+        Ok(event)
+    }
+
+    /// Parses a key and the colon after it
+    fn parse_key(&mut self) -> Result<Event<'a>> {
+        self.de.parse_whitespace();
+        let start = self.de.index;
+        let key = cow(self.de.parse_object_key()?);
+        self.token = start..self.de.index;
+        self.de.parse_object_colon()?;
+        self.expect = Expect::Value;
+        Ok(Event::Key(key))
+    }
+
+    /// Consumes the closing bracket or brace of the innermost container
+    fn close(&mut self) -> Event<'a> {
+        self.token = self.de.index..self.de.index + 1;
+        self.de.eat_char();
+        self.expect = Expect::Separator;
+        match self.stack.pop() {
+            Some(Container::Array) => Event::EndArray,
+            _ => Event::EndObject,
+        }
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Result<(Event<'a>, Range<usize>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_event() {
+            Ok(Some(event)) => Some(Ok((event, self.token.clone()))),
+            Ok(None) => None,
+            Err(err) => {
+                self.expect = Expect::Done;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+fn cow(s: StringLike<'_>) -> Cow<'_, str> {
+    match s {
+        StringLike::Borrowed(s) => Cow::Borrowed(s),
+        StringLike::Owned(s) => Cow::Owned(s),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use std::ops::Range;
+
+    use super::{Event, Tokenizer};
+    use crate::de::{Error, Result};
+
+    fn tokens(v: &[u8]) -> Result<Vec<(Event<'_>, Range<usize>)>> {
+        Tokenizer::new(v).collect()
+    }
+
+    fn events(v: &[u8]) -> Result<Vec<Event<'_>>> {
+        Tokenizer::new(v)
+            .map(|item| item.map(|(event, _)| event))
+            .collect()
+    }
+
+    #[test]
+    fn tokenizer_works() {
+        assert_eq!(
+            tokens(br#" {"a" : [1, -20, "x"], "b": {}, "c": [true, false, null]} "#),
+            Ok(vec![
+                (Event::StartObject, 1..2),
+                (Event::Key("a".into()), 2..5),
+                (Event::StartArray, 8..9),
+                (Event::Integer("1"), 9..10),
+                (Event::Integer("-20"), 12..15),
+                (Event::String("x".into()), 17..20),
+                (Event::EndArray, 20..21),
+                (Event::Key("b".into()), 23..26),
+                (Event::StartObject, 28..29),
+                (Event::EndObject, 29..30),
+                (Event::Key("c".into()), 32..35),
+                (Event::StartArray, 37..38),
+                (Event::Bool(true), 38..42),
+                (Event::Bool(false), 44..49),
+                (Event::Null, 51..55),
+                (Event::EndArray, 55..56),
+                (Event::EndObject, 56..57),
+            ])
+        );
+        assert_eq!(events(b"[]"), Ok(vec![Event::StartArray, Event::EndArray]));
+        assert_eq!(
+            events(b"123456789012345678901234567890"),
+            Ok(vec![Event::Integer("123456789012345678901234567890")])
+        );
+    }
+
+    #[test]
+    fn tokenizer_unescapes_strings() {
+        let events = events(br#"{"k\"ey": "a\u00e4\n", "plain": "b"}"#).unwrap();
+        assert_eq!(events[1], Event::Key(Cow::Owned("k\"ey".to_string())));
+        assert_eq!(events[2], Event::String(Cow::Owned("aä\n".to_string())));
+        assert!(matches!(events[3], Event::Key(Cow::Borrowed("plain"))));
+        assert!(matches!(events[4], Event::String(Cow::Borrowed("b"))));
+    }
+
+    #[test]
+    fn tokenizer_tracks_depth() {
+        let mut tokenizer = Tokenizer::new(b"[[{}]]");
+        let depths: Vec<usize> = std::iter::from_fn(|| {
+            tokenizer.next().map(|item| {
+                item.unwrap();
+                tokenizer.depth()
+            })
+        })
+        .collect();
+        assert_eq!(depths, [1, 2, 3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn tokenizer_errors() {
+        fn error(v: &[u8]) -> Error {
+            let mut tokenizer = Tokenizer::new(v);
+            let err = tokenizer.find_map(|item| item.err()).unwrap();
+            assert!(tokenizer.next().is_none());
+            err
+        }
+
+        assert_eq!(error(b""), Error::EofWhileParsingValue);
+        assert_eq!(error(b"  "), Error::EofWhileParsingValue);
+        assert_eq!(error(b"[1, 2"), Error::EofWhileParsingList);
+        assert_eq!(error(b"[1, 2,]"), Error::TrailingComma);
+        assert_eq!(error(b"[1 2]"), Error::ExpectedListCommaOrEnd);
+        assert_eq!(error(br#"{"a": 1"#), Error::EofWhileParsingObject);
+        assert_eq!(error(br#"{"a": 1,}"#), Error::TrailingComma);
+        assert_eq!(error(br#"{"a" 1}"#), Error::ExpectedColon);
+        assert_eq!(
+            error(br#"{"a": 1 "b": 2}"#),
+            Error::ExpectedObjectCommaOrEnd
+        );
+        assert_eq!(error(br#"{1: 2}"#), Error::KeyMustBeAString);
+        assert_eq!(error(b"[1.5]"), Error::ExpectedListCommaOrEnd);
+        assert_eq!(error(b"-"), Error::InvalidNumber);
+        assert_eq!(error(b"01"), Error::TrailingCharacters);
+        assert_eq!(error(b"tru"), Error::ExpectedSomeIdent);
+        assert_eq!(error(b"[}"), Error::ExpectedSomeValue);
+        assert_eq!(error(br#""\x""#), Error::InvalidEscape);
+        assert_eq!(error(br#""abc"#), Error::EofWhileParsingString);
+        assert_eq!(error(b"{} {}"), Error::TrailingCharacters);
+    }
+}
@@ -14,7 +14,19 @@ static SURROGARES_HIGH_LAST: u16 = 0xDBFF;
 static SURROGARES_LOW_FIRST: u16 = 0xDC00;
 static SURROGARES_LAST: u16 = 0xDFFF;
 
+/// The UTF-8 encoding of U+FFFD REPLACEMENT CHARACTER
+static REPLACEMENT_CHARACTER: &[u8] = "\u{FFFD}".as_bytes();
+
 pub(crate) fn unescape(source: &[u8]) -> Result<String> {
+    unescape_impl(source, false)
+}
+
+/// Like [`unescape`], but replaces each unpaired surrogate with U+FFFD instead of failing
+pub(crate) fn unescape_lossy(source: &[u8]) -> Result<String> {
+    unescape_impl(source, true)
+}
+
+fn unescape_impl(source: &[u8], lossy: bool) -> Result<String> {
     let mut out: Vec<u8> = Vec::with_capacity(source.len());
 
     let mut encoding_tmp = [0u8; 4];
@@ -42,7 +54,17 @@ pub(crate) fn unescape(source: &[u8]) -> Result<String> {
                         if codepoint >= SURROGARES_FIRST && codepoint <= SURROGARES_LAST {
                             if let Some(high) = high_surrogate {
                                 if codepoint < SURROGARES_LOW_FIRST {
-                                    return Err(Error::ExpectedLowSurrogate);
+                                    if !lossy {
+                                        return Err(Error::ExpectedLowSurrogate);
+                                    }
+                                    // Replace the previous high surrogate and wait for a low
+                                    // one for this one
+                                    out.extend_from_slice(REPLACEMENT_CHARACTER);
+                                    high_surrogate = Some(codepoint);
+                                    unicode_tmp_pos = 0;
+                                    in_unicode = false;
+                                    in_escape = false;
+                                    continue;
                                 }
                                 let low = codepoint;
 
@@ -56,13 +78,18 @@ pub(crate) fn unescape(source: &[u8]) -> Result<String> {
                                 out.extend_from_slice(encoded.as_bytes());
 
                                 high_surrogate = None;
-                            } else {
-                                if codepoint > SURROGARES_HIGH_LAST {
+                            } else if codepoint > SURROGARES_HIGH_LAST {
+                                if !lossy {
                                     return Err(Error::ExpectedHighSurrogate);
                                 }
+                                out.extend_from_slice(REPLACEMENT_CHARACTER);
+                            } else {
                                 high_surrogate = Some(codepoint);
                             }
                         } else {
+                            if lossy && high_surrogate.take().is_some() {
+                                out.extend_from_slice(REPLACEMENT_CHARACTER);
+                            }
                             let encoded = match char::try_from(codepoint as u32) {
                                 Ok(c) => c.encode_utf8(&mut encoding_tmp as &mut [u8]),
                                 Err(_) => return Err(Error::InvalidEscape),
@@ -78,6 +105,10 @@ pub(crate) fn unescape(source: &[u8]) -> Result<String> {
                 _ => return Err(Error::InvalidEscape),
             }
         } else if in_escape {
+            // Only `\u` can continue a surrogate pair
+            if lossy && *byte != b'u' && high_surrogate.take().is_some() {
+                out.extend_from_slice(REPLACEMENT_CHARACTER);
+            }
             match byte {
                 b'"' | b'/' | b'\\' => {
                     out.push(*byte);
@@ -114,8 +145,11 @@ pub(crate) fn unescape(source: &[u8]) -> Result<String> {
             if *byte == b'\\' {
                 in_escape = true;
             } else {
-                if high_surrogate.is_some() {
-                    return Err(Error::LoneSurrogateFound);
+                if high_surrogate.take().is_some() {
+                    if !lossy {
+                        return Err(Error::LoneSurrogateFound);
+                    }
+                    out.extend_from_slice(REPLACEMENT_CHARACTER);
                 }
 
                 out.push(*byte);
@@ -128,7 +162,10 @@ pub(crate) fn unescape(source: &[u8]) -> Result<String> {
     }
 
     if high_surrogate.is_some() {
-        return Err(Error::LoneSurrogateFound);
+        if !lossy {
+            return Err(Error::LoneSurrogateFound);
+        }
+        out.extend_from_slice(REPLACEMENT_CHARACTER);
     }
 
     String::from_utf8(out).map_err(|_| Error::InvalidUnicodeCodePoint)
@@ -330,6 +367,30 @@ mod tests {
         assert_eq!(uee(br#" \uD800\uE000 "#), Error::LoneSurrogateFound);
     }
 
+    #[test]
+    fn unescape_lossy_replaces_broken_surrogates() {
+        fn ul(source: &[u8]) -> String {
+            unescape_lossy(source).unwrap()
+        }
+
+        // Valid pairs are unchanged
+        assert_eq!(ul(br#"\uD83D\uDC4F"#), "\u{1F44F}");
+        // Unpaired high surrogate at the end, before text, before another escape
+        assert_eq!(ul(br#"a\uD83D"#), "a\u{FFFD}");
+        assert_eq!(ul(br#"\uD83Da"#), "\u{FFFD}a");
+        assert_eq!(ul(br#"\uD83D\n"#), "\u{FFFD}\n");
+        assert_eq!(ul(br#"\uD83D\u0041"#), "\u{FFFD}A");
+        // Two high surrogates, the second one is paired
+        assert_eq!(ul(br#"\uD83D\uD83D\uDC4F"#), "\u{FFFD}\u{1F44F}");
+        // Unpaired low surrogates
+        assert_eq!(ul(br#"\uDC4F"#), "\u{FFFD}");
+        assert_eq!(ul(br#"\uDC4F\uDC4Fx"#), "\u{FFFD}\u{FFFD}x");
+
+        // Other errors are still reported
+        assert_eq!(unescape_lossy(br#"\uD83"#), Err(Error::InvalidEscape));
+        assert_eq!(unescape_lossy(br#"\x"#), Err(Error::InvalidEscape));
+    }
+
     #[test]
     fn hex_decode_works() {
         assert_eq!(hex_decode([b'0', b'0', b'0', b'0']), 0x0000);
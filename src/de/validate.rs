@@ -0,0 +1,219 @@
+//! Validation of JSON text without a target type
+
+use crate::de::{Error, Event, Result, Tokenizer};
+
+/// Limits that [`validate_with_limits`] enforces on a document. The deserializers enforce them if
+/// they are set with [`Options::limits`](crate::de::Options::limits).
+///
+/// By default there are no limits. Each limit fails with its own error as soon as it is exceeded,
+/// before the offending value is allocated.
+///
+/// ```
+/// use serde_json_wasm::de::{validate_with_limits, Error, Limits};
+///
+/// let limits = Limits::new().max_depth(2).max_len(1024);
+/// assert_eq!(validate_with_limits(b"[[1]]", &limits), Ok(()));
+/// assert_eq!(
+///     validate_with_limits(b"[[[1]]]", &limits),
+///     Err(Error::DepthLimitExceeded)
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) max_len: Option<usize>,
+    pub(crate) max_string_len: Option<usize>,
+    pub(crate) max_array_len: Option<usize>,
+    pub(crate) max_object_len: Option<usize>,
+}
+
+impl Limits {
+    /// Creates limits that allow everything
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of nested arrays and objects
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Sets the maximum length of the input in bytes
+    pub fn max_len(mut self, len: usize) -> Self {
+        self.max_len = Some(len);
+        self
+    }
+
+    /// Sets the maximum length of strings and object keys in bytes. It is measured in the input,
+    /// i.e. between the quotes and before escape sequences are decoded.
+    pub fn max_string_len(mut self, len: usize) -> Self {
+        self.max_string_len = Some(len);
+        self
+    }
+
+    /// Sets the maximum number of elements of an array
+    pub fn max_array_len(mut self, len: usize) -> Self {
+        self.max_array_len = Some(len);
+        self
+    }
+
+    /// Sets the maximum number of members of an object
+    pub fn max_object_len(mut self, len: usize) -> Self {
+        self.max_object_len = Some(len);
+        self
+    }
+
+    pub(crate) fn check_input(&self, v: &[u8]) -> Result<()> {
+        match self.max_len {
+            Some(max) if v.len() > max => Err(Error::InputTooLong),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fails if an array that already has `len` elements cannot take another one
+    pub(crate) fn check_array_element(&self, len: usize) -> Result<()> {
+        match self.max_array_len {
+            Some(max) if len >= max => Err(Error::ArrayTooLong),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fails if an object that already has `len` members cannot take another one
+    pub(crate) fn check_object_member(&self, len: usize) -> Result<()> {
+        match self.max_object_len {
+            Some(max) if len >= max => Err(Error::ObjectTooLong),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Checks that `v` is exactly one JSON value that this crate can parse.
+///
+/// This applies the same rules as deserializing into a type, without needing one.
+///
+/// ```
+/// use serde_json_wasm::de::{validate, Error};
+///
+/// assert_eq!(validate(br#"{"amount": ["10", 20]}"#), Ok(()));
+/// assert_eq!(validate(br#"{"amount": 1.5}"#), Err(Error::ExpectedObjectCommaOrEnd));
+/// ```
+pub fn validate(v: &[u8]) -> Result<()> {
+    validate_with_limits(v, &Limits::default())
+}
+
+/// Checks that `v` is exactly one JSON value that this crate can parse and stays within `limits`.
+///
+/// See [`validate`].
+pub fn validate_with_limits(v: &[u8], limits: &Limits) -> Result<()> {
+    limits.check_input(v)?;
+
+    let max_depth = limits.max_depth.unwrap_or(usize::MAX);
+    let mut tokenizer = Tokenizer::with_limits(v, limits);
+    // Whether each open container is an array, and how many elements or members it has
+    let mut open: Vec<(bool, usize)> = Vec::new();
+    while let Some(item) = tokenizer.next() {
+        let (event, _) = item?;
+        if tokenizer.depth() > max_depth {
+            return Err(Error::DepthLimitExceeded);
+        }
+        match event {
+            Event::EndArray | Event::EndObject => {
+                open.pop();
+                continue;
+            }
+            Event::Key(_) => {
+                if let Some((_, len)) = open.last_mut() {
+                    limits.check_object_member(*len)?;
+                    *len += 1;
+                }
+                continue;
+            }
+            _ => {}
+        }
+        if let Some((true, len)) = open.last_mut() {
+            limits.check_array_element(*len)?;
+            *len += 1;
+        }
+        match event {
+            Event::StartArray => open.push((true, 0)),
+            Event::StartObject => open.push((false, 0)),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate, validate_with_limits, Limits};
+    use crate::de::Error;
+
+    #[test]
+    fn validate_works() {
+        assert_eq!(validate(b"null"), Ok(()));
+        assert_eq!(validate(b" [1, -2, \"x\\n\", true, {}] "), Ok(()));
+        assert_eq!(
+            validate(r#"{"a": {"b": [{"c": "😀"}]}}"#.as_bytes()),
+            Ok(())
+        );
+
+        assert_eq!(validate(b""), Err(Error::EofWhileParsingValue));
+        assert_eq!(validate(b"[1, 2,]"), Err(Error::TrailingComma));
+        assert_eq!(validate(b"[1] 2"), Err(Error::TrailingCharacters));
+        assert_eq!(validate(b"[tru]"), Err(Error::ExpectedSomeIdent));
+        assert_eq!(validate(b"[-]"), Err(Error::InvalidNumber));
+        assert_eq!(validate(br#"["\ud83d"]"#), Err(Error::LoneSurrogateFound));
+    }
+
+    #[test]
+    fn validate_with_limits_works() {
+        let limits = Limits::new().max_depth(2);
+        assert_eq!(validate_with_limits(b"1", &limits), Ok(()));
+        assert_eq!(validate_with_limits(br#"{"a": [1]}"#, &limits), Ok(()));
+        assert_eq!(
+            validate_with_limits(br#"{"a": [{}]}"#, &limits),
+            Err(Error::DepthLimitExceeded)
+        );
+        assert_eq!(
+            validate_with_limits(b"[[[", &limits),
+            Err(Error::DepthLimitExceeded)
+        );
+        assert_eq!(
+            validate_with_limits(b"[]", &Limits::new().max_depth(0)),
+            Err(Error::DepthLimitExceeded)
+        );
+
+        let limits = Limits::new().max_len(4);
+        assert_eq!(validate_with_limits(b"true", &limits), Ok(()));
+        assert_eq!(
+            validate_with_limits(b"true ", &limits),
+            Err(Error::InputTooLong)
+        );
+
+        let limits = Limits::new().max_string_len(3);
+        assert_eq!(validate_with_limits(br#"{"abc": "a\n"}"#, &limits), Ok(()));
+        assert_eq!(
+            validate_with_limits(br#"{"abcd": 1}"#, &limits),
+            Err(Error::StringTooLong)
+        );
+        assert_eq!(
+            validate_with_limits(br#"["a\n\t"]"#, &limits),
+            Err(Error::StringTooLong)
+        );
+
+        let limits = Limits::new().max_array_len(2).max_object_len(1);
+        assert_eq!(
+            validate_with_limits(br#"[[1, 2], {"a": [{}, []]}]"#, &limits),
+            Ok(())
+        );
+        assert_eq!(
+            validate_with_limits(b"[[1, 2, 3]]", &limits),
+            Err(Error::ArrayTooLong)
+        );
+        assert_eq!(
+            validate_with_limits(br#"[{"a": 1, "b": 2}]"#, &limits),
+            Err(Error::ObjectTooLong)
+        );
+    }
+}
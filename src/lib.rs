@@ -56,12 +56,15 @@
 #![deny(rust_2018_idioms)]
 
 pub mod de;
+mod raw;
 pub mod ser;
+pub mod transcode;
 
 #[doc(inline)]
 pub use self::de::{from_slice, from_str};
+pub use self::raw::RawValue;
 #[doc(inline)]
-pub use self::ser::{to_string, to_vec, to_string_pretty, to_vec_pretty};
+pub use self::ser::{to_string, to_string_pretty, to_vec, to_vec_pretty};
 
 #[cfg(test)]
 mod test {
@@ -121,12 +124,12 @@ mod test {
             },
             title: "Nice message".to_string(),
             content: Some("Happy \"blogging\" 👏\n\n\tCheers, I'm out\0\0\0".to_string()),
-            list: vec![0, 1, 2, 3, 42, 154841, std::u32::MAX],
+            list: vec![0, 1, 2, 3, 42, 154841, u32::MAX],
             published: true,
             comments: vec![CommentId(2), CommentId(700)],
             stats: Stats {
-                views: std::u64::MAX,
-                score: std::i64::MIN,
+                views: u64::MAX,
+                score: i64::MIN,
             },
             balances,
         };
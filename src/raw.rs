@@ -0,0 +1,247 @@
+//! Capturing and re-emitting unparsed JSON
+
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+/// Name of the newtype struct through which `RawValue` talks to our (de)serializers
+pub(crate) const TOKEN: &str = "$serde_json_wasm::private::RawValue";
+
+/// A piece of JSON text that is kept exactly as it appeared in the input.
+///
+/// When deserialized, a `RawValue` holds the validated text of the value at its position, without
+/// any whitespace around it. When serialized, that text is emitted verbatim. This allows
+/// forwarding a nested message without paying for deserializing and serializing it again, and
+/// without changing its bytes.
+///
+/// Use `&RawValue` to borrow from the input and `Box<RawValue>` to own the text.
+///
+/// ```
+/// use serde_derive::{Deserialize, Serialize};
+/// use serde_json_wasm::RawValue;
+///
+/// #[derive(Deserialize, Serialize)]
+/// struct Forward<'a> {
+///     contract: String,
+///     #[serde(borrow)]
+///     msg: &'a RawValue,
+/// }
+///
+/// let json = r#"{"contract":"juno1","msg":{"transfer": {"amount":"5"}}}"#;
+/// let forward: Forward = serde_json_wasm::from_str(json).unwrap();
+/// assert_eq!(forward.msg.get(), r#"{"transfer": {"amount":"5"}}"#);
+/// assert_eq!(serde_json_wasm::to_string(&forward).unwrap(), json);
+/// ```
+///
+/// This type only works with the (de)serializers of this crate.
+#[repr(transparent)]
+pub struct RawValue {
+    json: str,
+}
+
+impl RawValue {
+    pub(crate) fn from_borrowed(json: &str) -> &Self {
+        // SAFETY: `RawValue` is a transparent wrapper around `str`
+        unsafe { &*(json as *const str as *const RawValue) }
+    }
+
+    fn from_owned(json: Box<str>) -> Box<Self> {
+        // SAFETY: `RawValue` is a transparent wrapper around `str`
+        unsafe { Box::from_raw(Box::into_raw(json) as *mut RawValue) }
+    }
+
+    /// Creates a `RawValue` from a string of JSON text.
+    ///
+    /// Fails if the text is not exactly one well-formed JSON value. Surrounding whitespace is
+    /// allowed and kept.
+    pub fn from_string(json: String) -> crate::de::Result<Box<Self>> {
+        crate::de::check_value(json.as_bytes())?;
+        Ok(Self::from_owned(json.into_boxed_str()))
+    }
+
+    /// Returns the JSON text of this value
+    pub fn get(&self) -> &str {
+        &self.json
+    }
+}
+
+impl Clone for Box<RawValue> {
+    fn clone(&self) -> Self {
+        (**self).to_owned()
+    }
+}
+
+impl ToOwned for RawValue {
+    type Owned = Box<RawValue>;
+
+    fn to_owned(&self) -> Self::Owned {
+        RawValue::from_owned(self.json.to_owned().into_boxed_str())
+    }
+}
+
+impl fmt::Debug for RawValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RawValue").field(&&self.json).finish()
+    }
+}
+
+impl fmt::Display for RawValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.json)
+    }
+}
+
+impl Serialize for RawValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(TOKEN, &self.json)
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for &'a RawValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ReferenceVisitor;
+
+        impl<'de> Visitor<'de> for ReferenceVisitor {
+            type Value = &'de RawValue;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("any valid JSON value")
+            }
+
+            fn visit_borrowed_str<E>(self, json: &'de str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(RawValue::from_borrowed(json))
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(TOKEN, ReferenceVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Box<RawValue> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct BoxedVisitor;
+
+        impl<'de> Visitor<'de> for BoxedVisitor {
+            type Value = Box<RawValue>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("any valid JSON value")
+            }
+
+            fn visit_str<E>(self, json: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(RawValue::from_owned(json.to_owned().into_boxed_str()))
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(TOKEN, BoxedVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RawValue;
+    use crate::{from_str, to_string, to_string_pretty};
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Borrowed<'a> {
+        id: u32,
+        #[serde(borrow)]
+        msg: &'a RawValue,
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Owned {
+        id: u32,
+        msg: Box<RawValue>,
+    }
+
+    #[test]
+    fn deserialize_captures_exact_text() {
+        let json = r#"{ "id": 1, "msg":  {"a" : [1, "\u00e4", null]} }"#;
+        let borrowed: Borrowed<'_> = from_str(json).unwrap();
+        assert_eq!(borrowed.msg.get(), r#"{"a" : [1, "\u00e4", null]}"#);
+        let owned: Owned = from_str(json).unwrap();
+        assert_eq!(owned.msg.get(), r#"{"a" : [1, "\u00e4", null]}"#);
+
+        let scalars: Vec<&RawValue> = from_str(r#"[1, -20, "x", true, null]"#).unwrap();
+        let texts: Vec<&str> = scalars.iter().map(|raw| raw.get()).collect();
+        assert_eq!(texts, ["1", "-20", r#""x""#, "true", "null"]);
+    }
+
+    #[test]
+    fn deserialize_validates_captured_text() {
+        use crate::de::Error;
+
+        assert_eq!(
+            from_str::<Borrowed<'_>>(r#"{"id": 1, "msg": {"a": tru}}"#).unwrap_err(),
+            Error::ExpectedSomeIdent
+        );
+        assert_eq!(
+            from_str::<Borrowed<'_>>(r#"{"id": 1, "msg": [1,]}"#).unwrap_err(),
+            Error::TrailingComma
+        );
+        assert_eq!(
+            from_str::<Borrowed<'_>>(r#"{"id": 1, "msg": }"#).unwrap_err(),
+            Error::ExpectedSomeValue
+        );
+    }
+
+    #[test]
+    fn serialize_emits_text_verbatim() {
+        let json = r#"{"id":1,"msg":{"a" : [1, "\u00e4", null]}}"#;
+        let borrowed: Borrowed<'_> = from_str(json).unwrap();
+        assert_eq!(to_string(&borrowed).unwrap(), json);
+        let owned: Owned = from_str(json).unwrap();
+        assert_eq!(to_string(&owned).unwrap(), json);
+
+        assert_eq!(
+            to_string_pretty(&owned, b"  ").unwrap(),
+            "{\n  \"id\":1,\n  \"msg\":{\"a\" : [1, \"\\u00e4\", null]}\n}"
+        );
+    }
+
+    #[test]
+    fn from_string_validates() {
+        let raw = RawValue::from_string(" [1, 2] ".to_string()).unwrap();
+        assert_eq!(raw.get(), " [1, 2] ");
+        assert_eq!(to_string(&raw).unwrap(), " [1, 2] ");
+
+        assert!(RawValue::from_string("".to_string()).is_err());
+        assert!(RawValue::from_string("[1, 2".to_string()).is_err());
+        assert!(RawValue::from_string("1 2".to_string()).is_err());
+    }
+
+    #[test]
+    fn serialize_rejects_invalid_text() {
+        #[derive(Serialize)]
+        #[serde(rename = "$serde_json_wasm::private::RawValue")]
+        struct Forged(&'static str);
+
+        assert!(to_string(&Forged("{\"a\":1}")).is_ok());
+        assert_eq!(
+            to_string(&Forged("{\"a\":")),
+            Err(crate::ser::Error::InvalidRawValue)
+        );
+        assert_eq!(
+            to_string_pretty(&Forged("nul"), b"  "),
+            Err(crate::ser::Error::InvalidRawValue)
+        );
+    }
+}
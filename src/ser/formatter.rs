@@ -0,0 +1,286 @@
+use crate::ser::Options;
+
+/// An escape sequence the serializer needs inside a string
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CharEscape {
+    /// `"`, written as `\"`
+    Quote,
+    /// `\`, written as `\\`
+    ReverseSolidus,
+    /// Backspace (U+0008), written as `\b`
+    Backspace,
+    /// Form feed (U+000C), written as `\f`
+    FormFeed,
+    /// Line feed (U+000A), written as `\n`
+    LineFeed,
+    /// Carriage return (U+000D), written as `\r`
+    CarriageReturn,
+    /// Tab (U+0009), written as `\t`
+    Tab,
+    /// Any other control character (U+0000 to U+001F), written as `\u00XX`
+    AsciiControl(u8),
+    /// A UTF-16 code unit, written as `\uXXXX`. Used for characters that are escaped because of
+    /// [`Options::escape_non_ascii`](crate::ser::Options::escape_non_ascii) or
+    /// [`Options::escape_html`](crate::ser::Options::escape_html). Characters outside the Basic
+    /// Multilingual Plane are passed as two escapes, one per surrogate.
+    Unicode(u16),
+}
+
+impl CharEscape {
+    fn from_byte(byte: u8) -> Option<CharEscape> {
+        Some(match byte {
+            b'"' => CharEscape::Quote,
+            b'\\' => CharEscape::ReverseSolidus,
+            0x08 => CharEscape::Backspace,
+            0x0C => CharEscape::FormFeed,
+            b'\n' => CharEscape::LineFeed,
+            b'\r' => CharEscape::CarriageReturn,
+            b'\t' => CharEscape::Tab,
+            0x00..=0x1F => CharEscape::AsciiControl(byte),
+            _ => return None,
+        })
+    }
+}
+
+/// Upper-case hex for value in 0..16, encoded as ASCII bytes
+fn hex_4bit(c: u8) -> u8 {
+    if c <= 9 {
+        0x30 + c
+    } else {
+        0x41 + (c - 10)
+    }
+}
+
+/// Upper-case hex for value in 0..256, encoded as ASCII bytes
+fn hex(c: u8) -> (u8, u8) {
+    (hex_4bit(c >> 4), hex_4bit(c & 0x0F))
+}
+
+/// Controls the layout of the JSON text written by the serializer
+///
+/// The serializer decides what to write and calls these hooks, which append to `writer`. Every
+/// method has a default implementation that produces compact JSON, so a custom formatter only
+/// needs to override what it wants to change. [`CompactFormatter`] and
+/// [`PrettyFormatter`](crate::ser::PrettyFormatter) are the formatters used by
+/// [`to_vec`](crate::ser::to_vec) and [`to_vec_pretty`](crate::ser::to_vec_pretty).
+///
+/// ```
+/// use serde_json_wasm::ser::{to_string_with_formatter, Formatter, Options};
+///
+/// /// Puts a space after every comma
+/// struct Spaced;
+///
+/// impl Formatter for Spaced {
+///     fn begin_array_value(&mut self, writer: &mut Vec<u8>, first: bool) {
+///         if !first {
+///             writer.extend_from_slice(b", ");
+///         }
+///     }
+/// }
+///
+/// let json = to_string_with_formatter(&[1, 2, 3], Spaced, &Options::default()).unwrap();
+/// assert_eq!(json, "[1, 2, 3]");
+/// ```
+pub trait Formatter {
+    /// Writes `null`
+    fn write_null(&mut self, writer: &mut Vec<u8>) {
+        writer.extend_from_slice(b"null");
+    }
+
+    /// Writes `true` or `false`
+    fn write_bool(&mut self, writer: &mut Vec<u8>, value: bool) {
+        writer.extend_from_slice(if value { b"true" } else { b"false" });
+    }
+
+    /// Writes the decimal digits of an integer, with a leading `-` if it is negative.
+    ///
+    /// 128-bit integers are written as strings instead, through the string hooks.
+    fn write_integer(&mut self, writer: &mut Vec<u8>, digits: &str) {
+        writer.extend_from_slice(digits.as_bytes());
+    }
+
+    /// Writes the opening quote of a string
+    fn begin_string(&mut self, writer: &mut Vec<u8>) {
+        writer.push(b'"');
+    }
+
+    /// Writes the closing quote of a string
+    fn end_string(&mut self, writer: &mut Vec<u8>) {
+        writer.push(b'"');
+    }
+
+    /// Writes a part of a string that does not need escaping
+    fn write_string_fragment(&mut self, writer: &mut Vec<u8>, fragment: &str) {
+        writer.extend_from_slice(fragment.as_bytes());
+    }
+
+    /// Writes an escape sequence inside a string
+    fn write_char_escape(&mut self, writer: &mut Vec<u8>, escape: CharEscape) {
+        let sequence: &[u8] = match escape {
+            CharEscape::Quote => b"\\\"",
+            CharEscape::ReverseSolidus => b"\\\\",
+            CharEscape::Backspace => b"\\b",
+            CharEscape::FormFeed => b"\\f",
+            CharEscape::LineFeed => b"\\n",
+            CharEscape::CarriageReturn => b"\\r",
+            CharEscape::Tab => b"\\t",
+            CharEscape::AsciiControl(byte) => {
+                let (hex1, hex2) = hex(byte);
+                writer.extend_from_slice(&[b'\\', b'u', b'0', b'0', hex1, hex2]);
+                return;
+            }
+            CharEscape::Unicode(unit) => {
+                let (hex1, hex2) = hex((unit >> 8) as u8);
+                let (hex3, hex4) = hex(unit as u8);
+                writer.extend_from_slice(&[b'\\', b'u', hex1, hex2, hex3, hex4]);
+                return;
+            }
+        };
+        writer.extend_from_slice(sequence);
+    }
+
+    /// Writes the opening bracket of an array
+    fn begin_array(&mut self, writer: &mut Vec<u8>) {
+        writer.push(b'[');
+    }
+
+    /// Writes the closing bracket of an array
+    fn end_array(&mut self, writer: &mut Vec<u8>) {
+        writer.push(b']');
+    }
+
+    /// Called before each element of an array
+    fn begin_array_value(&mut self, writer: &mut Vec<u8>, first: bool) {
+        if !first {
+            writer.push(b',');
+        }
+    }
+
+    /// Called after each element of an array
+    fn end_array_value(&mut self, _writer: &mut Vec<u8>) {}
+
+    /// Writes the opening brace of an object
+    fn begin_object(&mut self, writer: &mut Vec<u8>) {
+        writer.push(b'{');
+    }
+
+    /// Writes the opening brace of an object that holds the fields of a struct. It is closed by
+    /// [`end_object`](Formatter::end_object) like any other object.
+    fn begin_struct(&mut self, writer: &mut Vec<u8>) {
+        self.begin_object(writer);
+    }
+
+    /// Writes the closing brace of an object
+    fn end_object(&mut self, writer: &mut Vec<u8>) {
+        writer.push(b'}');
+    }
+
+    /// Called before each key of an object
+    fn begin_object_key(&mut self, writer: &mut Vec<u8>, first: bool) {
+        if !first {
+            writer.push(b',');
+        }
+    }
+
+    /// Called after each key of an object
+    fn end_object_key(&mut self, _writer: &mut Vec<u8>) {}
+
+    /// Called before each value of an object, i.e. writes the colon
+    fn begin_object_value(&mut self, writer: &mut Vec<u8>) {
+        writer.push(b':');
+    }
+
+    /// Called after each value of an object
+    fn end_object_value(&mut self, _writer: &mut Vec<u8>) {}
+
+    /// Writes already formatted JSON text, e.g. the content of a [`RawValue`](crate::RawValue)
+    fn write_raw_fragment(&mut self, writer: &mut Vec<u8>, fragment: &str) {
+        writer.extend_from_slice(fragment.as_bytes());
+    }
+
+    /// Returns how many bytes at the end of `writer` the formatter may still remove, e.g. when it
+    /// rewrites an open array on one line. The serializer subtracts them from the output length
+    /// before it checks [`Options::max_output_len`](crate::ser::Options::max_output_len).
+    fn reclaimable_len(&self, _writer: &[u8]) -> usize {
+        0
+    }
+}
+
+/// Formatter that writes JSON without any whitespace
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CompactFormatter;
+
+impl Formatter for CompactFormatter {}
+
+/// Writes `value` as the content of a string, escaping where necessary
+pub(crate) fn write_escaped<F>(
+    writer: &mut Vec<u8>,
+    formatter: &mut F,
+    value: &str,
+    options: &Options,
+) where
+    F: ?Sized + Formatter,
+{
+    // Do escaping according to "6. MUST represent all strings (including object member names) in
+    // their minimal-length UTF-8 encoding": https://gibson042.github.io/canonicaljson-spec/
+    //
+    // We don't need to escape lone surrogates because surrogate pairs do not exist in valid UTF-8,
+    // even if they can exist in JSON or JavaScript strings (UCS-2 based). As a result, lone surrogates
+    // cannot exist in a Rust String. If they do, the bug is in the String constructor.
+    // An excellent explanation is available at https://www.youtube.com/watch?v=HhIEDWmQS3w
+    //
+    // All bytes that need escaping are ASCII, so the fragments in between are valid UTF-8.
+    if options.escapes_more() {
+        return write_escaped_more(writer, formatter, value, options);
+    }
+    let bytes = value.as_bytes();
+    let mut start = 0;
+    for (i, &byte) in bytes.iter().enumerate() {
+        if let Some(escape) = CharEscape::from_byte(byte) {
+            if start < i {
+                formatter.write_string_fragment(writer, &value[start..i]);
+            }
+            formatter.write_char_escape(writer, escape);
+            start = i + 1;
+        }
+    }
+    if start < bytes.len() {
+        formatter.write_string_fragment(writer, &value[start..]);
+    }
+}
+
+/// Like [`write_escaped`], but also escapes the characters selected by `options`
+fn write_escaped_more<F>(writer: &mut Vec<u8>, formatter: &mut F, value: &str, options: &Options)
+where
+    F: ?Sized + Formatter,
+{
+    let mut start = 0;
+    for (i, c) in value.char_indices() {
+        let escape = if c.is_ascii() {
+            CharEscape::from_byte(c as u8)
+        } else {
+            None
+        };
+        let escape_unicode = options.escapes_char(c);
+        if escape.is_none() && !escape_unicode {
+            continue;
+        }
+
+        if start < i {
+            formatter.write_string_fragment(writer, &value[start..i]);
+        }
+        match escape {
+            Some(escape) => formatter.write_char_escape(writer, escape),
+            None => {
+                let mut units = [0u16; 2];
+                for &unit in c.encode_utf16(&mut units).iter() {
+                    formatter.write_char_escape(writer, CharEscape::Unicode(unit));
+                }
+            }
+        }
+        start = i + c.len_utf8();
+    }
+    if start < value.len() {
+        formatter.write_string_fragment(writer, &value[start..]);
+    }
+}
@@ -2,101 +2,102 @@ use std::fmt;
 
 use serde::{ser, Serialize};
 
-use crate::ser::{Error, Result, Serializer};
+use crate::ser::{Error, Formatter, Result, Serializer};
 
 use super::{seq::SerializeSeq, struct_::SerializeStruct, Unreachable};
 
-pub struct SerializeMap<'a> {
-    ser: &'a mut Serializer,
+pub struct SerializeMap<'a, F> {
+    ser: &'a mut Serializer<F>,
     first: bool,
 }
 
-impl<'a> SerializeMap<'a> {
-    pub(crate) fn new(ser: &'a mut Serializer) -> Self {
+impl<'a, F> SerializeMap<'a, F>
+where
+    F: Formatter,
+{
+    pub(crate) fn new(ser: &'a mut Serializer<F>) -> Self {
         SerializeMap { ser, first: true }
     }
 }
 
-impl<'a> ser::SerializeMap for SerializeMap<'a> {
+impl<'a, F> ser::SerializeMap for SerializeMap<'a, F>
+where
+    F: Formatter,
+{
     type Ok = ();
     type Error = Error;
 
     fn end(self) -> Result<Self::Ok> {
-        self.ser.buf.push(b'}');
-        Ok(())
+        self.ser.formatter.end_object(&mut self.ser.buf);
+        self.ser.check_len()
     }
 
-    fn serialize_key<T: ?Sized>(&mut self, key: &T) -> Result<()>
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
     where
-        T: ser::Serialize,
+        T: ?Sized + ser::Serialize,
     {
-        if !self.first {
-            self.ser.buf.push(b',');
-        }
+        self.ser
+            .formatter
+            .begin_object_key(&mut self.ser.buf, self.first);
         self.first = false;
         // Use key serializer to unsure key type validity.
-        key.serialize(MapKeySerializer { ser: self.ser })?;
-        self.ser.buf.extend_from_slice(b":");
+        key.serialize(MapKeySerializer {
+            ser: &mut *self.ser,
+        })?;
+        self.ser.formatter.end_object_key(&mut self.ser.buf);
         Ok(())
     }
 
-    fn serialize_value<T: ?Sized>(&mut self, value: &T) -> Result<()>
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
     where
-        T: ser::Serialize,
+        T: ?Sized + ser::Serialize,
     {
+        self.ser.formatter.begin_object_value(&mut self.ser.buf);
         value.serialize(&mut *self.ser)?;
+        self.ser.formatter.end_object_value(&mut self.ser.buf);
         Ok(())
     }
 }
 
 /// Wrapper around Serializer that only allows serialization of valid JSON key types (strings).
-struct MapKeySerializer<'a> {
-    ser: &'a mut Serializer,
-}
-
-pub(crate) fn key_must_be_a_string() -> Error {
-    Error::Custom("JSON object key is required to be a string type.".to_string())
+struct MapKeySerializer<'a, F> {
+    ser: &'a mut Serializer<F>,
 }
 
 macro_rules! serialize_unsigned_key {
     ($self:ident, $N:expr, $v:expr) => {{
         let ser = $self.ser;
-        ser.buf.push(b'"');
-        let res: Result<Self::Ok> = super::serialize_unsigned!(ser, $N, $v);
-        res?;
-        ser.buf.push(b'"');
-        Ok(())
+        super::serialize_unsigned!(ser, $N, $v, true)
     }};
 }
 
 macro_rules! serialize_signed_key {
     ($self:ident, $N:expr, $v:expr, $ixx:ident, $uxx:ident) => {{
         let ser = $self.ser;
-        ser.buf.push(b'"');
-        let res: Result<Self::Ok> = super::serialize_signed!(ser, $N, $v, $ixx, $uxx);
-        res?;
-        ser.buf.push(b'"');
-        Ok(())
+        super::serialize_signed!(ser, $N, $v, $ixx, $uxx, true)
     }};
 }
 
-impl<'a> ser::Serializer for MapKeySerializer<'a> {
+impl<'a, F> ser::Serializer for MapKeySerializer<'a, F>
+where
+    F: Formatter,
+{
     type Ok = ();
     type Error = Error;
-    type SerializeSeq = SerializeSeq<'a>;
-    type SerializeTuple = SerializeSeq<'a>;
+    type SerializeSeq = SerializeSeq<'a, F>;
+    type SerializeTuple = SerializeSeq<'a, F>;
     type SerializeTupleStruct = Unreachable;
-    type SerializeTupleVariant = SerializeSeq<'a>;
-    type SerializeMap = SerializeMap<'a>;
-    type SerializeStruct = SerializeStruct<'a>;
-    type SerializeStructVariant = SerializeStruct<'a>;
+    type SerializeTupleVariant = SerializeSeq<'a, F>;
+    type SerializeMap = SerializeMap<'a, F>;
+    type SerializeStruct = SerializeStruct<'a, F>;
+    type SerializeStructVariant = SerializeStruct<'a, F>;
 
     fn serialize_bool(self, _value: bool) -> Result<()> {
-        Err(key_must_be_a_string())
+        Err(Error::KeyMustBeAString)
     }
     #[inline]
     fn serialize_str(self, value: &str) -> Result<()> {
-        self.ser.serialize_str(value)
+        self.ser.write_str(value)
     }
 
     #[inline]
@@ -106,7 +107,7 @@ impl<'a> ser::Serializer for MapKeySerializer<'a> {
         _variant_index: u32,
         variant: &'static str,
     ) -> Result<()> {
-        self.ser.serialize_str(variant)
+        self.ser.write_str(variant)
     }
 
     #[inline]
@@ -158,27 +159,27 @@ impl<'a> ser::Serializer for MapKeySerializer<'a> {
     }
 
     fn serialize_f32(self, _value: f32) -> Result<()> {
-        Err(key_must_be_a_string())
+        Err(Error::KeyMustBeAString)
     }
 
     fn serialize_f64(self, _value: f64) -> Result<()> {
-        Err(key_must_be_a_string())
+        Err(Error::KeyMustBeAString)
     }
 
     fn serialize_char(self, value: char) -> Result<()> {
-        self.ser.serialize_str(&value.to_string())
+        self.ser.write_str(&value.to_string())
     }
 
     fn serialize_bytes(self, _value: &[u8]) -> Result<()> {
-        Err(key_must_be_a_string())
+        Err(Error::KeyMustBeAString)
     }
 
     fn serialize_unit(self) -> Result<()> {
-        Err(key_must_be_a_string())
+        Err(Error::KeyMustBeAString)
     }
 
     fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
-        Err(key_must_be_a_string())
+        Err(Error::KeyMustBeAString)
     }
 
     fn serialize_newtype_variant<T>(
@@ -191,26 +192,26 @@ impl<'a> ser::Serializer for MapKeySerializer<'a> {
     where
         T: ?Sized + Serialize,
     {
-        Err(key_must_be_a_string())
+        Err(Error::KeyMustBeAString)
     }
 
     fn serialize_none(self) -> Result<()> {
-        Err(key_must_be_a_string())
+        Err(Error::KeyMustBeAString)
     }
 
     fn serialize_some<T>(self, _value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
-        Err(key_must_be_a_string())
+        Err(Error::KeyMustBeAString)
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
-        Err(key_must_be_a_string())
+        Err(Error::KeyMustBeAString)
     }
 
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
-        Err(key_must_be_a_string())
+        Err(Error::KeyMustBeAString)
     }
 
     fn serialize_tuple_struct(
@@ -218,7 +219,7 @@ impl<'a> ser::Serializer for MapKeySerializer<'a> {
         _name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
-        Err(key_must_be_a_string())
+        Err(Error::KeyMustBeAString)
     }
 
     fn serialize_tuple_variant(
@@ -228,15 +229,15 @@ impl<'a> ser::Serializer for MapKeySerializer<'a> {
         _variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
-        Err(key_must_be_a_string())
+        Err(Error::KeyMustBeAString)
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
-        Err(key_must_be_a_string())
+        Err(Error::KeyMustBeAString)
     }
 
     fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
-        Err(key_must_be_a_string())
+        Err(Error::KeyMustBeAString)
     }
 
     fn serialize_struct_variant(
@@ -246,7 +247,7 @@ impl<'a> ser::Serializer for MapKeySerializer<'a> {
         _variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        Err(key_must_be_a_string())
+        Err(Error::KeyMustBeAString)
     }
 
     fn collect_str<T>(self, _value: &T) -> Result<()>
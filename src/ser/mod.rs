@@ -6,40 +6,87 @@ use serde::ser;
 
 use std::vec::Vec;
 
+pub(crate) use self::formatter::write_escaped;
 use self::map::SerializeMap;
+use self::options::EmptyObject;
+use self::raw::RawValueEmitter;
 use self::seq::SerializeSeq;
 use self::struct_::SerializeStruct;
 
+mod formatter;
 mod map;
+mod none;
+mod options;
 mod pretty;
+mod raw;
 mod seq;
 mod struct_;
 
-pub use pretty::{to_string_pretty, to_vec_pretty};
+pub use formatter::{CharEscape, CompactFormatter, Formatter};
+pub use options::{Options, UnitVariantStyle};
+pub use pretty::{
+    to_string_pretty, to_string_pretty_with_config, to_string_pretty_with_options, to_vec_pretty,
+    to_vec_pretty_with_config, to_vec_pretty_with_options, Newline, PrettyConfig, PrettyFormatter,
+};
 
 /// Serialization result
 pub type Result<T> = ::core::result::Result<T, Error>;
 
 /// This type represents all possible errors that can occur when serializing JSON data
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum Error {
-    /// Buffer is full
-    BufferFull,
+    /// The indent of a [`PrettyConfig`] or one passed to a pretty-printing function contains
+    /// characters other than JSON whitespace.
+    InvalidIndent,
+
+    /// A [`RawValue`](crate::RawValue) does not hold exactly one valid JSON value.
+    InvalidRawValue,
+
+    /// A custom [`Formatter`] wrote bytes that are not valid UTF-8, so the output cannot be
+    /// returned as a `String`.
+    InvalidUtf8,
+
+    /// A map key is not a string, an integer or a unit variant.
+    KeyMustBeAString,
+
+    /// The output would be longer than allowed.
+    OutputLimitExceeded,
+
+    /// The value has a type that cannot be written as JSON by this crate, like a float. Holds the
+    /// name of the type.
+    UnsupportedType(&'static str),
 
     /// Custom error message from serde
     Custom(String),
 }
 
-impl From<()> for Error {
-    fn from(_: ()) -> Error {
-        Error::BufferFull
-    }
-}
-
-impl From<u8> for Error {
-    fn from(_: u8) -> Error {
-        Error::BufferFull
+impl Error {
+    /// Returns a number that identifies the kind of error and does not change between versions of
+    /// this crate, unlike the message.
+    ///
+    /// | Code | Variant |
+    /// |------|---------|
+    /// | 200 | [`Custom`](Error::Custom) |
+    /// | 202 | [`InvalidIndent`](Error::InvalidIndent) |
+    /// | 203 | [`InvalidRawValue`](Error::InvalidRawValue) |
+    /// | 204 | [`KeyMustBeAString`](Error::KeyMustBeAString) |
+    /// | 205 | [`OutputLimitExceeded`](Error::OutputLimitExceeded) |
+    /// | 206 | [`UnsupportedType`](Error::UnsupportedType) |
+    /// | 207 | [`InvalidUtf8`](Error::InvalidUtf8) |
+    ///
+    /// Code 201 belonged to the removed `BufferFull` variant and is not reused. Codes of
+    /// [`de::Error`](crate::de::Error) start at 100.
+    pub fn code(&self) -> u16 {
+        match self {
+            Error::Custom(_) => 200,
+            Error::InvalidIndent => 202,
+            Error::InvalidRawValue => 203,
+            Error::KeyMustBeAString => 204,
+            Error::OutputLimitExceeded => 205,
+            Error::UnsupportedType(_) => 206,
+            Error::InvalidUtf8 => 207,
+        }
     }
 }
 
@@ -56,7 +103,14 @@ impl error::Error for Error {
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Error::BufferFull => write!(f, "Buffer is full"),
+            Error::InvalidIndent => write!(f, "Indent must only contain JSON whitespace."),
+            Error::InvalidRawValue => write!(f, "RawValue does not contain a valid JSON value."),
+            Error::InvalidUtf8 => write!(f, "Formatter wrote invalid UTF-8."),
+            Error::KeyMustBeAString => {
+                write!(f, "JSON object key is required to be a string type.")
+            }
+            Error::OutputLimitExceeded => write!(f, "The output is longer than allowed."),
+            Error::UnsupportedType(ty) => write!(f, "Cannot serialize values of type `{}`.", ty),
             Error::Custom(msg) => write!(f, "{}", &msg),
         }
     }
@@ -64,25 +118,95 @@ impl fmt::Display for Error {
 
 /// Serializer implements serde::ser::Serializer and allows us to serialize a
 /// serde struct into JSON
-pub struct Serializer {
+///
+/// The layout of the output is controlled by a [`Formatter`].
+pub struct Serializer<F = CompactFormatter> {
     buf: Vec<u8>,
+    formatter: F,
+    options: Options,
 }
 
 /// Number of bytes reserved by default for the output JSON
 static INITIAL_CAPACITY: usize = 1024;
 
-impl Serializer {
-    fn new() -> Self {
+impl<F> Serializer<F>
+where
+    F: Formatter,
+{
+    fn new(formatter: F, options: &Options) -> Self {
         Serializer {
             buf: Vec::with_capacity(INITIAL_CAPACITY),
+            formatter,
+            options: options.clone(),
+        }
+    }
+
+    /// Writes the digits produced by `serialize_unsigned!` and `serialize_signed!`, as a string
+    /// if `quoted` is set
+    fn write_integer(&mut self, digits: &[u8], quoted: bool) -> Result<()> {
+        // SAFETY: the digits and the minus sign are ASCII
+        let digits = unsafe { std::str::from_utf8_unchecked(digits) };
+        if quoted {
+            self.formatter.begin_string(&mut self.buf);
+            self.formatter.write_string_fragment(&mut self.buf, digits);
+            self.formatter.end_string(&mut self.buf);
+        } else {
+            self.formatter.write_integer(&mut self.buf, digits);
         }
+        self.check_len()
+    }
+
+    /// Writes a string, escaping its content
+    fn write_str(&mut self, v: &str) -> Result<()> {
+        // The escaped string is at least as long as `v`, so a long string fails before it is copied
+        if let Some(max) = self.options.max_output_len {
+            if self.output_len() + v.len() > max {
+                return Err(Error::OutputLimitExceeded);
+            }
+        }
+        self.formatter.begin_string(&mut self.buf);
+        write_escaped(&mut self.buf, &mut self.formatter, v, &self.options);
+        self.formatter.end_string(&mut self.buf);
+        self.check_len()
+    }
+
+    /// Length of the output, without what the formatter is going to remove again
+    fn output_len(&self) -> usize {
+        self.buf
+            .len()
+            .saturating_sub(self.formatter.reclaimable_len(&self.buf))
+    }
+
+    /// Fails once the output is longer than `max_output_len`
+    fn check_len(&self) -> Result<()> {
+        match self.options.max_output_len {
+            Some(max) if self.output_len() > max => Err(Error::OutputLimitExceeded),
+            _ => Ok(()),
+        }
+    }
+
+    /// Opens the object around the content of a newtype, tuple or struct variant
+    fn begin_variant(&mut self, variant: &str) -> Result<()> {
+        self.formatter.begin_object(&mut self.buf);
+        self.formatter.begin_object_key(&mut self.buf, true);
+        self.write_str(variant)?;
+        self.formatter.end_object_key(&mut self.buf);
+        self.formatter.begin_object_value(&mut self.buf);
+        Ok(())
+    }
+
+    /// Closes the object opened by `begin_variant`
+    fn end_variant(&mut self) -> Result<()> {
+        self.formatter.end_object_value(&mut self.buf);
+        self.formatter.end_object(&mut self.buf);
+        self.check_len()
     }
 }
 
 // NOTE(serialize_*signed) This is basically the numtoa implementation minus the lookup tables,
 // which take 200+ bytes of ROM / Flash
 macro_rules! serialize_unsigned {
-    ($self:ident, $N:expr, $v:expr) => {{
+    ($self:ident, $N:expr, $v:expr, $quoted:expr) => {{
         let mut buf = [0u8; $N];
 
         let mut v = $v;
@@ -98,18 +222,17 @@ macro_rules! serialize_unsigned {
             }
         }
 
-        $self.buf.extend_from_slice(&buf[i..]);
-        Ok(())
+        $self.write_integer(&buf[i..], $quoted)
     }};
 }
 // Export for use in map
 pub(crate) use serialize_unsigned;
 
 macro_rules! serialize_signed {
-    ($self:ident, $N:expr, $v:expr, $ixx:ident, $uxx:ident) => {{
+    ($self:ident, $N:expr, $v:expr, $ixx:ident, $uxx:ident, $quoted:expr) => {{
         let v = $v;
-        let (signed, mut v) = if v == $ixx::min_value() {
-            (true, $ixx::max_value() as $uxx + 1)
+        let (signed, mut v) = if v == $ixx::MIN {
+            (true, $ixx::MAX as $uxx + 1)
         } else if v < 0 {
             (true, -v as $uxx)
         } else {
@@ -134,198 +257,109 @@ macro_rules! serialize_signed {
         } else {
             i += 1;
         }
-        $self.buf.extend_from_slice(&buf[i..]);
-        Ok(())
+        $self.write_integer(&buf[i..], $quoted)
     }};
 }
 // Export for use in map
 pub(crate) use serialize_signed;
 
-/// Upper-case hex for value in 0..16, encoded as ASCII bytes
-fn hex_4bit(c: u8) -> u8 {
-    if c <= 9 {
-        0x30 + c
-    } else {
-        0x41 + (c - 10)
-    }
-}
-
-/// Upper-case hex for value in 0..256, encoded as ASCII bytes
-fn hex(c: u8) -> (u8, u8) {
-    (hex_4bit(c >> 4), hex_4bit(c & 0x0F))
-}
-
-impl<'a> ser::Serializer for &'a mut Serializer {
+impl<'a, F> ser::Serializer for &'a mut Serializer<F>
+where
+    F: Formatter,
+{
     type Ok = ();
     type Error = Error;
-    type SerializeSeq = SerializeSeq<'a>;
-    type SerializeTuple = SerializeSeq<'a>;
+    type SerializeSeq = SerializeSeq<'a, F>;
+    type SerializeTuple = SerializeSeq<'a, F>;
     type SerializeTupleStruct = Unreachable;
-    type SerializeTupleVariant = SerializeSeq<'a>;
-    type SerializeMap = SerializeMap<'a>;
-    type SerializeStruct = SerializeStruct<'a>;
-    type SerializeStructVariant = SerializeStruct<'a>;
+    type SerializeTupleVariant = SerializeSeq<'a, F>;
+    type SerializeMap = SerializeMap<'a, F>;
+    type SerializeStruct = SerializeStruct<'a, F>;
+    type SerializeStructVariant = SerializeStruct<'a, F>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
-        if v {
-            self.buf.extend_from_slice(b"true");
-        } else {
-            self.buf.extend_from_slice(b"false");
-        }
-        Ok(())
+        self.formatter.write_bool(&mut self.buf, v);
+        self.check_len()
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
         // -128
-        serialize_signed!(self, 4, v, i8, u8)
+        serialize_signed!(self, 4, v, i8, u8, false)
     }
 
     fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
         // -32768
-        serialize_signed!(self, 6, v, i16, u16)
+        serialize_signed!(self, 6, v, i16, u16, false)
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
         // -2147483648
-        serialize_signed!(self, 11, v, i32, u32)
+        serialize_signed!(self, 11, v, i32, u32, false)
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
         // -9223372036854775808
-        serialize_signed!(self, 20, v, i64, u64)
+        serialize_signed!(self, 20, v, i64, u64, false)
     }
 
     fn serialize_i128(self, v: i128) -> Result<Self::Ok> {
         // -170141183460469231731687303715884105728
-        self.buf.push(b'"');
-        let res: Result<Self::Ok> = serialize_signed!(self, 40, v, i128, u128);
-        res?;
-        self.buf.push(b'"');
-        Ok(())
+        serialize_signed!(self, 40, v, i128, u128, true)
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
         // 255
-        serialize_unsigned!(self, 3, v)
+        serialize_unsigned!(self, 3, v, false)
     }
 
     fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
         // 65535
-        serialize_unsigned!(self, 5, v)
+        serialize_unsigned!(self, 5, v, false)
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
         // 4294967295
-        serialize_unsigned!(self, 10, v)
+        serialize_unsigned!(self, 10, v, false)
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
         // 18446744073709551615
-        serialize_unsigned!(self, 20, v)
+        serialize_unsigned!(self, 20, v, false)
     }
 
     fn serialize_u128(self, v: u128) -> Result<Self::Ok> {
         // 340282366920938463463374607431768211455
-        self.buf.push(b'"');
-        let res: Result<Self::Ok> = serialize_unsigned!(self, 39, v);
-        res?;
-        self.buf.push(b'"');
-        Ok(())
+        serialize_unsigned!(self, 39, v, true)
     }
 
     fn serialize_f32(self, _v: f32) -> Result<Self::Ok> {
-        unreachable!()
+        Err(Error::UnsupportedType("f32"))
     }
 
     fn serialize_f64(self, _v: f64) -> Result<Self::Ok> {
-        unreachable!()
+        Err(Error::UnsupportedType("f64"))
     }
 
     fn serialize_char(self, _v: char) -> Result<Self::Ok> {
-        unreachable!()
+        Err(Error::UnsupportedType("char"))
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok> {
-        self.buf.push(b'"');
-
-        // Do escaping according to "6. MUST represent all strings (including object member names) in
-        // their minimal-length UTF-8 encoding": https://gibson042.github.io/canonicaljson-spec/
-        //
-        // We don't need to escape lone surrogates because surrogate pairs do not exist in valid UTF-8,
-        // even if they can exist in JSON or JavaScript strings (UCS-2 based). As a result, lone surrogates
-        // cannot exist in a Rust String. If they do, the bug is in the String constructor.
-        // An excellent explanation is available at https://www.youtube.com/watch?v=HhIEDWmQS3w
-
-        // Temporary storage for encoded a single char.
-        // A char is up to 4 bytes long when encoded to UTF-8.
-        let mut encoding_tmp = [0u8; 4];
-
-        for c in v.chars() {
-            match c {
-                '\\' => {
-                    self.buf.push(b'\\');
-                    self.buf.push(b'\\');
-                }
-                '"' => {
-                    self.buf.push(b'\\');
-                    self.buf.push(b'"');
-                }
-                '\u{0008}' => {
-                    self.buf.push(b'\\');
-                    self.buf.push(b'b');
-                }
-                '\u{0009}' => {
-                    self.buf.push(b'\\');
-                    self.buf.push(b't');
-                }
-                '\u{000A}' => {
-                    self.buf.push(b'\\');
-                    self.buf.push(b'n');
-                }
-                '\u{000C}' => {
-                    self.buf.push(b'\\');
-                    self.buf.push(b'f');
-                }
-                '\u{000D}' => {
-                    self.buf.push(b'\\');
-                    self.buf.push(b'r');
-                }
-                '\u{0000}'..='\u{001F}' => {
-                    self.buf.push(b'\\');
-                    self.buf.push(b'u');
-                    self.buf.push(b'0');
-                    self.buf.push(b'0');
-                    let (hex1, hex2) = hex(c as u8);
-                    self.buf.push(hex1);
-                    self.buf.push(hex2);
-                }
-                _ => {
-                    if c.len_utf8() == 1 {
-                        self.buf.push(c as u8);
-                    } else {
-                        let encoded = c.encode_utf8(&mut encoding_tmp as &mut [u8]);
-                        self.buf.extend_from_slice(encoded.as_bytes());
-                    }
-                }
-            }
-        }
-
-        self.buf.push(b'"');
-        Ok(())
+        self.write_str(v)
     }
 
     fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok> {
-        unreachable!()
+        Err(Error::UnsupportedType("bytes"))
     }
 
     fn serialize_none(self) -> Result<Self::Ok> {
-        self.buf.extend_from_slice(b"null");
-        Ok(())
+        self.formatter.write_null(&mut self.buf);
+        self.check_len()
     }
 
-    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok>
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok>
     where
-        T: ser::Serialize,
+        T: ?Sized + ser::Serialize,
     {
         value.serialize(self)
     }
@@ -333,33 +367,49 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     fn serialize_unit(self) -> Result<Self::Ok> {
         // The unit type is a zero element tuple, so the consistent way to serialize this would be "[]".
         // However, for compatibility with serde_json we serialize to "null".
-        self.buf.extend_from_slice(b"null");
-        Ok(())
+        self.formatter.write_null(&mut self.buf);
+        self.check_len()
     }
 
     fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
         // Unit struct is serialized to (serde_json compatible) "null"
-        self.buf.extend_from_slice(b"null");
-        Ok(())
+        self.formatter.write_null(&mut self.buf);
+        self.check_len()
     }
 
     fn serialize_unit_variant(
         self,
-        _name: &'static str,
-        _variant_index: u32,
+        name: &'static str,
+        variant_index: u32,
         variant: &'static str,
     ) -> Result<Self::Ok> {
-        self.serialize_str(variant)
+        match self.options.unit_variant_style {
+            UnitVariantStyle::String => self.serialize_str(variant),
+            UnitVariantStyle::EmptyObject => {
+                self.serialize_newtype_variant(name, variant_index, variant, &EmptyObject)
+            }
+            UnitVariantStyle::Null => {
+                self.serialize_newtype_variant(name, variant_index, variant, &())
+            }
+        }
     }
 
-    fn serialize_newtype_struct<T: ?Sized>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Self::Ok>
     where
-        T: ser::Serialize,
+        T: ?Sized + ser::Serialize,
     {
+        if name == crate::raw::TOKEN {
+            value.serialize(RawValueEmitter {
+                buf: &mut self.buf,
+                formatter: &mut self.formatter,
+                options: &self.options,
+            })?;
+            return self.check_len();
+        }
         value.serialize(&mut *self)
     }
 
-    fn serialize_newtype_variant<T: ?Sized>(
+    fn serialize_newtype_variant<T>(
         self,
         _name: &'static str,
         _variant_index: u32,
@@ -367,18 +417,15 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         value: &T,
     ) -> Result<Self::Ok>
     where
-        T: ser::Serialize,
+        T: ?Sized + ser::Serialize,
     {
-        self.buf.push(b'{');
-        self.serialize_str(variant)?;
-        self.buf.push(b':');
+        self.begin_variant(variant)?;
         value.serialize(&mut *self)?;
-        self.buf.push(b'}');
-        Ok(())
+        self.end_variant()
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
-        self.buf.push(b'[');
+        self.formatter.begin_array(&mut self.buf);
 
         Ok(SerializeSeq::new(self))
     }
@@ -392,7 +439,7 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         _name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
-        unreachable!()
+        Err(Error::UnsupportedType("tuple struct"))
     }
 
     fn serialize_tuple_variant(
@@ -402,19 +449,17 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
-        self.buf.push(b'{');
-        self.serialize_str(variant)?;
-        self.buf.push(b':');
+        self.begin_variant(variant)?;
         self.serialize_tuple(len)
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
-        self.buf.push(b'{');
+        self.formatter.begin_object(&mut self.buf);
         Ok(SerializeMap::new(self))
     }
 
     fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
-        self.buf.push(b'{');
+        self.formatter.begin_struct(&mut self.buf);
 
         Ok(SerializeStruct::new(self))
     }
@@ -426,9 +471,7 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        self.buf.push(b'{');
-        self.serialize_str(variant)?;
-        self.buf.push(b':');
+        self.begin_variant(variant)?;
         self.serialize_struct(name, len)
     }
 }
@@ -438,9 +481,7 @@ pub fn to_string<T>(value: &T) -> Result<String>
 where
     T: ser::Serialize + ?Sized,
 {
-    let mut ser = Serializer::new();
-    value.serialize(&mut ser)?;
-    Ok(unsafe { String::from_utf8_unchecked(ser.buf) })
+    to_string_with_options(value, &Options::default())
 }
 
 /// Serializes the given data structure as a JSON byte vector
@@ -448,7 +489,44 @@ pub fn to_vec<T>(value: &T) -> Result<Vec<u8>>
 where
     T: ser::Serialize + ?Sized,
 {
-    let mut ser = Serializer::new();
+    to_vec_with_options(value, &Options::default())
+}
+
+/// Serializes the given data structure as a string of JSON text, using the given options
+pub fn to_string_with_options<T>(value: &T, options: &Options) -> Result<String>
+where
+    T: ser::Serialize + ?Sized,
+{
+    let buf = to_vec_with_options(value, options)?;
+    Ok(unsafe { String::from_utf8_unchecked(buf) })
+}
+
+/// Serializes the given data structure as a JSON byte vector, using the given options
+pub fn to_vec_with_options<T>(value: &T, options: &Options) -> Result<Vec<u8>>
+where
+    T: ser::Serialize + ?Sized,
+{
+    to_vec_with_formatter(value, CompactFormatter, options)
+}
+
+/// Serializes the given data structure as a string of JSON text laid out by `formatter`
+pub fn to_string_with_formatter<T, F>(value: &T, formatter: F, options: &Options) -> Result<String>
+where
+    T: ser::Serialize + ?Sized,
+    F: Formatter,
+{
+    let buf = to_vec_with_formatter(value, formatter, options)?;
+    // The serializer only writes valid UTF-8, but a custom formatter may not
+    String::from_utf8(buf).map_err(|_| Error::InvalidUtf8)
+}
+
+/// Serializes the given data structure as a JSON byte vector laid out by `formatter`
+pub fn to_vec_with_formatter<T, F>(value: &T, formatter: F, options: &Options) -> Result<Vec<u8>>
+where
+    T: ser::Serialize + ?Sized,
+    F: Formatter,
+{
+    let mut ser = Serializer::new(formatter, options);
     value.serialize(&mut ser)?;
     Ok(ser.buf)
 }
@@ -466,6 +544,32 @@ impl ser::Error for Error {
 /// (and should be unreachable, unless you use unsupported serde flags)
 pub enum Unreachable {}
 
+impl ser::SerializeSeq for Unreachable {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized>(&mut self, _value: &T) -> Result<()> {
+        unreachable!()
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        unreachable!()
+    }
+}
+
+impl ser::SerializeTuple for Unreachable {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized>(&mut self, _value: &T) -> Result<()> {
+        unreachable!()
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        unreachable!()
+    }
+}
+
 impl ser::SerializeTupleStruct for Unreachable {
     type Ok = ();
     type Error = Error;
@@ -496,16 +600,32 @@ impl ser::SerializeMap for Unreachable {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_key<T: ?Sized>(&mut self, _key: &T) -> Result<()>
+    fn serialize_key<T>(&mut self, _key: &T) -> Result<()>
     where
-        T: ser::Serialize,
+        T: ?Sized + ser::Serialize,
     {
         unreachable!()
     }
 
-    fn serialize_value<T: ?Sized>(&mut self, _value: &T) -> Result<()>
+    fn serialize_value<T>(&mut self, _value: &T) -> Result<()>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        unreachable!()
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        unreachable!()
+    }
+}
+
+impl ser::SerializeStruct for Unreachable {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, _key: &'static str, _value: &T) -> Result<()>
     where
-        T: ser::Serialize,
+        T: ?Sized + ser::Serialize,
     {
         unreachable!()
     }
@@ -519,9 +639,9 @@ impl ser::SerializeStructVariant for Unreachable {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(&mut self, _key: &'static str, _value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, _key: &'static str, _value: &T) -> Result<()>
     where
-        T: ser::Serialize,
+        T: ?Sized + ser::Serialize,
     {
         unreachable!()
     }
@@ -544,41 +664,175 @@ mod tests {
         assert_eq!(to_string(&false).unwrap(), "false");
     }
 
+    #[test]
+    fn error_codes() {
+        use super::Error;
+
+        assert_eq!(Error::Custom("x".to_string()).code(), 200);
+        assert_eq!(Error::InvalidIndent.code(), 202);
+        assert_eq!(Error::InvalidUtf8.code(), 207);
+    }
+
+    #[test]
+    fn formatter_writes_invalid_utf8() {
+        use super::{to_string_with_formatter, to_vec_with_formatter, Error, Formatter, Options};
+
+        struct Latin1;
+
+        impl Formatter for Latin1 {
+            fn write_null(&mut self, writer: &mut Vec<u8>) {
+                writer.push(0xe9);
+            }
+        }
+
+        let options = Options::default();
+        assert_eq!(to_vec_with_formatter(&(), Latin1, &options), Ok(vec![0xe9]));
+        assert_eq!(
+            to_string_with_formatter(&(), Latin1, &options),
+            Err(Error::InvalidUtf8)
+        );
+    }
+
+    #[test]
+    fn max_output_len() {
+        use super::{to_string_with_options, Error, Options};
+        use crate::ser::{to_string_pretty_with_config, PrettyConfig};
+
+        #[derive(Serialize)]
+        struct Response {
+            data: Vec<String>,
+            done: bool,
+        }
+
+        let response = Response {
+            data: vec!["abc".to_string(); 3],
+            done: true,
+        };
+        let json = r#"{"data":["abc","abc","abc"],"done":true}"#;
+        let options = Options::new().max_output_len(json.len());
+        assert_eq!(to_string_with_options(&response, &options).unwrap(), json);
+        let options = Options::new().max_output_len(json.len() - 1);
+        assert_eq!(
+            to_string_with_options(&response, &options),
+            Err(Error::OutputLimitExceeded)
+        );
+
+        // Fails before the long string is written
+        let long = "x".repeat(1 << 20);
+        let options = Options::new().max_output_len(100);
+        assert_eq!(
+            to_string_with_options(&("a", long), &options),
+            Err(Error::OutputLimitExceeded)
+        );
+        assert_eq!(
+            to_string_with_options(&[[[[(); 0]; 1]; 5]; 5], &Options::new().max_output_len(40)),
+            Err(Error::OutputLimitExceeded)
+        );
+
+        // The pretty layout and the trailing newline count as well
+        let pretty = to_string_pretty_with_config(
+            &response,
+            &PrettyConfig::new().trailing_newline(true),
+            &Options::default(),
+        )
+        .unwrap();
+        let options = Options::new().max_output_len(pretty.len());
+        let config = PrettyConfig::new().trailing_newline(true);
+        assert_eq!(
+            to_string_pretty_with_config(&response, &config, &options),
+            Ok(pretty.clone())
+        );
+        let options = Options::new().max_output_len(pretty.len() - 1);
+        assert_eq!(
+            to_string_pretty_with_config(&response, &config, &options),
+            Err(Error::OutputLimitExceeded)
+        );
+
+        // Arrays put on one line count with their final length
+        let numbers: Vec<u32> = (1..=8).collect();
+        let config = PrettyConfig::new().inline_arrays(80);
+        let inline = "[1, 2, 3, 4, 5, 6, 7, 8]";
+        let options = Options::new().max_output_len(inline.len());
+        assert_eq!(
+            to_string_pretty_with_config(&numbers, &config, &options),
+            Ok(inline.to_string())
+        );
+        let options = Options::new().max_output_len(inline.len() - 1);
+        assert_eq!(
+            to_string_pretty_with_config(&numbers, &config, &options),
+            Err(Error::OutputLimitExceeded)
+        );
+        let nested = to_string_pretty_with_config(&[&numbers], &config, &Options::new()).unwrap();
+        assert_eq!(nested, "[\n  [1, 2, 3, 4, 5, 6, 7, 8]\n]");
+        let options = Options::new().max_output_len(nested.len());
+        assert_eq!(
+            to_string_pretty_with_config(&[&numbers], &config, &options),
+            Ok(nested)
+        );
+        // An array too wide for one line keeps its multi-line length
+        let config = PrettyConfig::new().inline_arrays(10);
+        let options = Options::new().max_output_len(inline.len());
+        assert_eq!(
+            to_string_pretty_with_config(&numbers, &config, &options),
+            Err(Error::OutputLimitExceeded)
+        );
+    }
+
+    #[test]
+    fn unsupported_types() {
+        use super::Error;
+
+        #[derive(Serialize)]
+        struct Point(i8, i8);
+
+        assert_eq!(to_string(&1.5f32), Err(Error::UnsupportedType("f32")));
+        assert_eq!(to_string(&[0.5f64]), Err(Error::UnsupportedType("f64")));
+        assert_eq!(to_string(&'a'), Err(Error::UnsupportedType("char")));
+        assert_eq!(
+            to_string(&Point(1, 2)),
+            Err(Error::UnsupportedType("tuple struct"))
+        );
+        assert_eq!(
+            Error::UnsupportedType("f32").to_string(),
+            "Cannot serialize values of type `f32`."
+        );
+    }
+
     #[test]
     fn number() {
         assert_eq!(to_string::<u8>(&0).unwrap(), "0");
         assert_eq!(to_string::<u8>(&1).unwrap(), "1");
-        assert_eq!(to_string::<u8>(&std::u8::MAX).unwrap(), "255");
+        assert_eq!(to_string::<u8>(&u8::MAX).unwrap(), "255");
 
         assert_eq!(to_string::<i8>(&0).unwrap(), "0");
         assert_eq!(to_string::<i8>(&1).unwrap(), "1");
         assert_eq!(to_string::<i8>(&127).unwrap(), "127");
         assert_eq!(to_string::<i8>(&-1).unwrap(), "-1");
-        assert_eq!(to_string::<i8>(&std::i8::MIN).unwrap(), "-128");
+        assert_eq!(to_string::<i8>(&i8::MIN).unwrap(), "-128");
 
         assert_eq!(to_string::<u16>(&0).unwrap(), "0");
         assert_eq!(to_string::<u16>(&1).unwrap(), "1");
         assert_eq!(to_string::<u16>(&550).unwrap(), "550");
-        assert_eq!(to_string::<u16>(&std::u16::MAX).unwrap(), "65535");
+        assert_eq!(to_string::<u16>(&u16::MAX).unwrap(), "65535");
 
         assert_eq!(to_string::<i16>(&0).unwrap(), "0");
         assert_eq!(to_string::<i16>(&1).unwrap(), "1");
         assert_eq!(to_string::<i16>(&550).unwrap(), "550");
-        assert_eq!(to_string::<i16>(&std::i16::MAX).unwrap(), "32767");
+        assert_eq!(to_string::<i16>(&i16::MAX).unwrap(), "32767");
         assert_eq!(to_string::<i16>(&-1).unwrap(), "-1");
-        assert_eq!(to_string::<i16>(&std::i16::MIN).unwrap(), "-32768");
+        assert_eq!(to_string::<i16>(&i16::MIN).unwrap(), "-32768");
 
         assert_eq!(to_string::<u32>(&0).unwrap(), "0");
         assert_eq!(to_string::<u32>(&1).unwrap(), "1");
         assert_eq!(to_string::<u32>(&456789).unwrap(), "456789");
-        assert_eq!(to_string::<u32>(&std::u32::MAX).unwrap(), "4294967295");
+        assert_eq!(to_string::<u32>(&u32::MAX).unwrap(), "4294967295");
 
         assert_eq!(to_string::<i32>(&0).unwrap(), "0");
         assert_eq!(to_string::<i32>(&1).unwrap(), "1");
         assert_eq!(to_string::<i32>(&456789).unwrap(), "456789");
-        assert_eq!(to_string::<i32>(&std::i32::MAX).unwrap(), "2147483647");
+        assert_eq!(to_string::<i32>(&i32::MAX).unwrap(), "2147483647");
         assert_eq!(to_string::<i32>(&-1).unwrap(), "-1");
-        assert_eq!(to_string::<i32>(&std::i32::MIN).unwrap(), "-2147483648");
+        assert_eq!(to_string::<i32>(&i32::MIN).unwrap(), "-2147483648");
 
         assert_eq!(to_string::<u64>(&0).unwrap(), "0");
         assert_eq!(to_string::<u64>(&1).unwrap(), "1");
@@ -593,10 +847,7 @@ mod tests {
             to_string::<u64>(&9007199254740992).unwrap(),
             "9007199254740992"
         ); // Number.MAX_SAFE_INTEGER+1
-        assert_eq!(
-            to_string::<u64>(&std::u64::MAX).unwrap(),
-            "18446744073709551615"
-        );
+        assert_eq!(to_string::<u64>(&u64::MAX).unwrap(), "18446744073709551615");
 
         assert_eq!(to_string::<i64>(&0).unwrap(), "0");
         assert_eq!(to_string::<i64>(&1).unwrap(), "1");
@@ -611,15 +862,9 @@ mod tests {
             to_string::<i64>(&9007199254740992).unwrap(),
             "9007199254740992"
         ); // Number.MAX_SAFE_INTEGER+1
-        assert_eq!(
-            to_string::<i64>(&std::i64::MAX).unwrap(),
-            "9223372036854775807"
-        );
+        assert_eq!(to_string::<i64>(&i64::MAX).unwrap(), "9223372036854775807");
         assert_eq!(to_string::<i64>(&-1).unwrap(), "-1");
-        assert_eq!(
-            to_string::<i64>(&std::i64::MIN).unwrap(),
-            "-9223372036854775808"
-        );
+        assert_eq!(to_string::<i64>(&i64::MIN).unwrap(), "-9223372036854775808");
 
         assert_eq!(to_string::<u128>(&0).unwrap(), r#""0""#);
         assert_eq!(to_string::<u128>(&1).unwrap(), r#""1""#);
@@ -643,7 +888,7 @@ mod tests {
             r#""9223372036854775808""#
         );
         assert_eq!(
-            to_string::<u128>(&std::u128::MAX).unwrap(),
+            to_string::<u128>(&u128::MAX).unwrap(),
             r#""340282366920938463463374607431768211455""#
         );
 
@@ -669,12 +914,12 @@ mod tests {
             r#""9223372036854775808""#
         );
         assert_eq!(
-            to_string::<i128>(&std::i128::MAX).unwrap(),
+            to_string::<i128>(&i128::MAX).unwrap(),
             r#""170141183460469231731687303715884105727""#
         );
         assert_eq!(to_string::<i128>(&-1).unwrap(), r#""-1""#);
         assert_eq!(
-            to_string::<i128>(&std::i128::MIN).unwrap(),
+            to_string::<i128>(&i128::MIN).unwrap(),
             r#""-170141183460469231731687303715884105728""#
         );
     }
@@ -715,7 +960,7 @@ mod tests {
 
         type BigPair = (u128, u128);
 
-        let pair: BigPair = (std::u128::MAX, std::u128::MAX);
+        let pair: BigPair = (u128::MAX, u128::MAX);
 
         assert_eq!(
             to_string(&pair).unwrap(),
@@ -755,6 +1000,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn enum_variants_unit_like_with_options() {
+        use super::{to_string_with_options, Options, UnitVariantStyle};
+        use std::collections::BTreeMap;
+
+        #[derive(Serialize)]
+        enum Op {
+            Exit,
+            Enter(u8),
+        }
+
+        let options = Options::new().unit_variant_style(UnitVariantStyle::EmptyObject);
+        assert_eq!(
+            to_string_with_options(&Op::Exit, &options).unwrap(),
+            r#"{"Exit":{}}"#
+        );
+        assert_eq!(
+            to_string_with_options(&[Op::Exit, Op::Enter(1)], &options).unwrap(),
+            r#"[{"Exit":{}},{"Enter":1}]"#
+        );
+
+        let options = Options::new().unit_variant_style(UnitVariantStyle::Null);
+        assert_eq!(
+            to_string_with_options(&Op::Exit, &options).unwrap(),
+            r#"{"Exit":null}"#
+        );
+
+        let options = Options::new().unit_variant_style(UnitVariantStyle::String);
+        assert_eq!(
+            to_string_with_options(&Op::Exit, &options).unwrap(),
+            r#""Exit""#
+        );
+
+        // Map keys stay strings
+        #[derive(Serialize, PartialEq, Eq, PartialOrd, Ord)]
+        enum Key {
+            A,
+        }
+        let mut map = BTreeMap::new();
+        map.insert(Key::A, Op::Exit);
+        let options = Options::new().unit_variant_style(UnitVariantStyle::EmptyObject);
+        assert_eq!(
+            to_string_with_options(&map, &options).unwrap(),
+            r#"{"A":{"Exit":{}}}"#
+        );
+    }
+
     #[test]
     fn enum_variants_tuple_like_structs() {
         #[derive(Serialize)]
@@ -866,6 +1158,60 @@ mod tests {
         assert_eq!(to_string(" \u{001f} ").unwrap(), r#"" \u001F ""#);
     }
 
+    #[test]
+    fn str_escape_modes() {
+        use super::{to_string_pretty_with_options, to_string_with_options, Options};
+        use std::collections::BTreeMap;
+
+        let ascii = Options::new().escape_non_ascii(true);
+        assert_eq!(
+            to_string_with_options("a\u{e4}\u{9ec}\u{211d}\u{1f4a3}\n", &ascii).unwrap(),
+            r#""a\u00E4\u09EC\u211D\uD83D\uDCA3\n""#
+        );
+        assert_eq!(
+            to_string_with_options("<a href='x'>&</a>", &ascii).unwrap(),
+            r#""<a href='x'>&</a>""#
+        );
+
+        let html = Options::new().escape_html(true);
+        assert_eq!(
+            to_string_with_options("</script>&\u{2028}\u{2029}\u{e4}\"", &html).unwrap(),
+            "\"\\u003C/script\\u003E\\u0026\\u2028\\u2029\u{e4}\\\"\""
+        );
+
+        // Keys of maps and fields of structs are escaped as well
+        #[derive(Serialize)]
+        struct Tag {
+            #[serde(rename = "<tag>")]
+            tag: BTreeMap<&'static str, &'static str>,
+        }
+        let mut tag = BTreeMap::new();
+        tag.insert("\u{e9}", "\u{f6}");
+        let both = ascii.escape_html(true);
+        assert_eq!(
+            to_string_with_options(&Tag { tag: tag.clone() }, &both).unwrap(),
+            r#"{"\u003Ctag\u003E":{"\u00E9":"\u00F6"}}"#
+        );
+        assert_eq!(
+            to_string_pretty_with_options(&Tag { tag }, b" ", &both).unwrap(),
+            "{\n \"\\u003Ctag\\u003E\":{\n  \"\\u00E9\":\"\\u00F6\"\n }\n}"
+        );
+
+        // The strings inside raw values as well
+        let raw = crate::RawValue::from_string(
+            "{\"<p>\": [\"</script>\", \"\u{e9}\u{1f4a3}\\u00e4\"]}".to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            to_string_with_options(&raw, &both).unwrap(),
+            r#"{"\u003Cp\u003E": ["\u003C/script\u003E", "\u00E9\uD83D\uDCA3\u00e4"]}"#
+        );
+        assert_eq!(
+            to_string_with_options(&raw, &Options::new()).unwrap(),
+            raw.get()
+        );
+    }
+
     #[test]
     fn collect_str_can_be_used_in_custom_seralize_impl() {
         struct SpecialType {
@@ -959,13 +1305,64 @@ mod tests {
             r#"{"description":"An ambient temperature sensor"}"#
         );
 
-        // XXX Ideally this should produce "{}"
+        // `None` is written as `null` unless `skip_none_fields` is set
         assert_eq!(
             to_string(&Property { description: None }).unwrap(),
             r#"{"description":null}"#
         );
     }
 
+    #[test]
+    fn struct_skip_none_fields() {
+        use super::{to_string_with_options, Options};
+
+        #[derive(Serialize)]
+        struct Response {
+            a: Option<u8>,
+            b: Option<Option<u8>>,
+            c: Option<u8>,
+            d: (),
+            e: Vec<Option<u8>>,
+        }
+
+        #[derive(Serialize)]
+        enum Msg {
+            Update { x: Option<u8>, y: Option<u8> },
+        }
+
+        let options = Options::new().skip_none_fields(true);
+        let value = Response {
+            a: None,
+            b: Some(None),
+            c: None,
+            d: (),
+            e: vec![None],
+        };
+        assert_eq!(
+            to_string_with_options(&value, &options).unwrap(),
+            r#"{"b":null,"d":null,"e":[null]}"#
+        );
+        assert_eq!(
+            to_string(&value).unwrap(),
+            r#"{"a":null,"b":null,"c":null,"d":null,"e":[null]}"#
+        );
+        assert_eq!(
+            to_string_with_options(
+                &Msg::Update {
+                    x: None,
+                    y: Some(1)
+                },
+                &options
+            )
+            .unwrap(),
+            r#"{"Update":{"y":1}}"#
+        );
+        assert_eq!(
+            to_string_with_options(&Msg::Update { x: None, y: None }, &options).unwrap(),
+            r#"{"Update":{}}"#
+        );
+    }
+
     #[test]
     fn struct_u8() {
         #[derive(Serialize)]
@@ -1011,6 +1408,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn struct_field_names_are_escaped() {
+        use serde::ser::SerializeStruct;
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Quoted {
+            #[serde(rename = "a\"b")]
+            a: u8,
+            #[serde(rename = "tab\there")]
+            b: u8,
+            #[serde(rename = "caf\u{e9}")]
+            c: u8,
+        }
+
+        let value = Quoted { a: 1, b: 2, c: 3 };
+        let json = to_string(&value).unwrap();
+        assert_eq!(json, "{\"a\\\"b\":1,\"tab\\there\":2,\"caf\u{e9}\":3}");
+        assert_eq!(serde_json::to_string(&value).unwrap(), json);
+        assert_eq!(crate::from_str::<Quoted>(&json).unwrap(), value);
+
+        struct HandWritten;
+
+        impl Serialize for HandWritten {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                let mut s = serializer.serialize_struct("HandWritten", 1)?;
+                s.serialize_field("back\\slash", &())?;
+                s.end()
+            }
+        }
+
+        assert_eq!(to_string(&HandWritten).unwrap(), r#"{"back\\slash":null}"#);
+        assert_eq!(
+            crate::to_string_pretty(&HandWritten, b" ").unwrap(),
+            "{\n \"back\\\\slash\":null\n}"
+        );
+    }
+
     #[test]
     fn struct_with_flatten() {
         #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -1221,7 +1658,7 @@ mod tests {
 
     #[test]
     fn invalid_json_key() {
-        use crate::ser::map::key_must_be_a_string;
+        use crate::ser::Error;
         use std::collections::HashMap;
 
         #[derive(Debug, Serialize, PartialEq, Eq, Hash)]
@@ -1240,10 +1677,7 @@ mod tests {
         // unit enum
         let mut map = HashMap::new();
         map.insert(MyResult::Unit(()), "my_age");
-        assert_eq!(
-            to_string(&map).unwrap_err().to_string(),
-            key_must_be_a_string().to_string()
-        );
+        assert_eq!(to_string(&map).unwrap_err(), Error::KeyMustBeAString);
 
         // struct enum
         let mut map = HashMap::new();
@@ -1255,10 +1689,7 @@ mod tests {
             }),
             "my_age",
         );
-        assert_eq!(
-            to_string(&map).unwrap_err().to_string(),
-            key_must_be_a_string().to_string()
-        );
+        assert_eq!(to_string(&map).unwrap_err(), Error::KeyMustBeAString);
 
         // Struct
         let mut map = HashMap::new();
@@ -1270,10 +1701,7 @@ mod tests {
             },
             "my_age",
         );
-        assert_eq!(
-            to_string(&map).unwrap_err().to_string(),
-            key_must_be_a_string().to_string()
-        );
+        assert_eq!(to_string(&map).unwrap_err(), Error::KeyMustBeAString);
     }
 
     #[test]
@@ -0,0 +1,211 @@
+use std::fmt;
+
+use serde::ser::{self, Impossible, Serialize};
+
+/// Returns true if `value` serializes as `None`
+pub(crate) fn is_none<T>(value: &T) -> bool
+where
+    T: ?Sized + Serialize,
+{
+    value.serialize(NoneProbe).is_ok()
+}
+
+/// Serializer that only accepts `None`. Every other value stops at the first call with an error.
+struct NoneProbe;
+
+#[derive(Debug)]
+struct NotNone;
+
+impl fmt::Display for NotNone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("not None")
+    }
+}
+
+impl std::error::Error for NotNone {}
+
+impl ser::Error for NotNone {
+    fn custom<T>(_msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        NotNone
+    }
+}
+
+type Result<T = ()> = ::core::result::Result<T, NotNone>;
+
+impl ser::Serializer for NoneProbe {
+    type Ok = ();
+    type Error = NotNone;
+    type SerializeSeq = Impossible<(), NotNone>;
+    type SerializeTuple = Impossible<(), NotNone>;
+    type SerializeTupleStruct = Impossible<(), NotNone>;
+    type SerializeTupleVariant = Impossible<(), NotNone>;
+    type SerializeMap = Impossible<(), NotNone>;
+    type SerializeStruct = Impossible<(), NotNone>;
+    type SerializeStructVariant = Impossible<(), NotNone>;
+
+    fn serialize_none(self) -> Result {
+        Ok(())
+    }
+
+    fn serialize_bool(self, _value: bool) -> Result {
+        Err(NotNone)
+    }
+
+    fn serialize_i8(self, _value: i8) -> Result {
+        Err(NotNone)
+    }
+
+    fn serialize_i16(self, _value: i16) -> Result {
+        Err(NotNone)
+    }
+
+    fn serialize_i32(self, _value: i32) -> Result {
+        Err(NotNone)
+    }
+
+    fn serialize_i64(self, _value: i64) -> Result {
+        Err(NotNone)
+    }
+
+    fn serialize_i128(self, _value: i128) -> Result {
+        Err(NotNone)
+    }
+
+    fn serialize_u8(self, _value: u8) -> Result {
+        Err(NotNone)
+    }
+
+    fn serialize_u16(self, _value: u16) -> Result {
+        Err(NotNone)
+    }
+
+    fn serialize_u32(self, _value: u32) -> Result {
+        Err(NotNone)
+    }
+
+    fn serialize_u64(self, _value: u64) -> Result {
+        Err(NotNone)
+    }
+
+    fn serialize_u128(self, _value: u128) -> Result {
+        Err(NotNone)
+    }
+
+    fn serialize_f32(self, _value: f32) -> Result {
+        Err(NotNone)
+    }
+
+    fn serialize_f64(self, _value: f64) -> Result {
+        Err(NotNone)
+    }
+
+    fn serialize_char(self, _value: char) -> Result {
+        Err(NotNone)
+    }
+
+    fn serialize_str(self, _value: &str) -> Result {
+        Err(NotNone)
+    }
+
+    fn serialize_bytes(self, _value: &[u8]) -> Result {
+        Err(NotNone)
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(NotNone)
+    }
+
+    fn serialize_unit(self) -> Result {
+        Err(NotNone)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result {
+        Err(NotNone)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result {
+        Err(NotNone)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, _value: &T) -> Result
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(NotNone)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(NotNone)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(NotNone)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(NotNone)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(NotNone)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(NotNone)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(NotNone)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(NotNone)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(NotNone)
+    }
+
+    fn collect_str<T>(self, _value: &T) -> Result
+    where
+        T: ?Sized + fmt::Display,
+    {
+        Err(NotNone)
+    }
+}
@@ -0,0 +1,137 @@
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+/// Options that change the JSON produced by the serializers
+///
+/// The default options produce the same output as [`to_vec`](crate::ser::to_vec) and
+/// [`to_vec_pretty`](crate::ser::to_vec_pretty).
+///
+/// ```
+/// use serde_derive::Serialize;
+/// use serde_json_wasm::ser::{to_string_with_options, Options, UnitVariantStyle};
+///
+/// #[derive(Serialize)]
+/// #[serde(rename_all = "snake_case")]
+/// enum ExecuteMsg {
+///     Pause,
+/// }
+///
+/// let options = Options::new().unit_variant_style(UnitVariantStyle::EmptyObject);
+/// assert_eq!(
+///     to_string_with_options(&ExecuteMsg::Pause, &options).unwrap(),
+///     r#"{"pause":{}}"#
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Options {
+    pub(crate) unit_variant_style: UnitVariantStyle,
+    pub(crate) escape_non_ascii: bool,
+    pub(crate) escape_html: bool,
+    pub(crate) skip_none_fields: bool,
+    pub(crate) max_output_len: Option<usize>,
+}
+
+impl Options {
+    /// Creates the default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how unit enum variants are represented. Map keys are always written as strings.
+    pub fn unit_variant_style(mut self, style: UnitVariantStyle) -> Self {
+        self.unit_variant_style = style;
+        self
+    }
+
+    /// Sets whether all non-ASCII characters in strings and keys are escaped as `\uXXXX`, so that
+    /// the output is pure ASCII. Characters outside the Basic Multilingual Plane are written as a
+    /// UTF-16 surrogate pair, e.g. `"\uD83D\uDCA3"`. This also applies to the strings in the
+    /// text of a [`RawValue`](crate::RawValue).
+    pub fn escape_non_ascii(mut self, enabled: bool) -> Self {
+        self.escape_non_ascii = enabled;
+        self
+    }
+
+    /// Sets whether `<`, `>`, `&`, U+2028 and U+2029 in strings and keys are escaped as `\uXXXX`,
+    /// so that the output can be embedded into HTML and JavaScript sources. This also applies to
+    /// the strings in the text of a [`RawValue`](crate::RawValue).
+    pub fn escape_html(mut self, enabled: bool) -> Self {
+        self.escape_html = enabled;
+        self
+    }
+
+    /// Sets whether fields of structs and struct variants whose value is `None` are left out, as
+    /// if they were annotated with `#[serde(skip_serializing_if = "Option::is_none")]`. Map
+    /// entries and sequence elements are not affected.
+    pub fn skip_none_fields(mut self, enabled: bool) -> Self {
+        self.skip_none_fields = enabled;
+        self
+    }
+
+    /// Sets the maximum length of the output in bytes. Serialization stops with
+    /// [`Error::OutputLimitExceeded`](crate::ser::Error::OutputLimitExceeded) as soon as the
+    /// output grows beyond it, so an unexpectedly large value is never serialized completely.
+    ///
+    /// The limit applies to the compact and pretty-printed output and to custom formatters.
+    /// One-line arrays of [`PrettyConfig::inline_arrays`](crate::ser::PrettyConfig::inline_arrays)
+    /// are measured in their final form. There is no fixed-buffer serializer in this crate, the
+    /// output always goes to a `Vec`, so this option is the way to bound its size.
+    ///
+    /// ```
+    /// use serde_json_wasm::ser::{to_vec_with_options, Error, Options};
+    ///
+    /// let options = Options::new().max_output_len(8);
+    /// assert_eq!(to_vec_with_options(&[1, 2, 3], &options).unwrap(), b"[1,2,3]");
+    /// assert_eq!(
+    ///     to_vec_with_options(&[1, 2, 3, 4], &options),
+    ///     Err(Error::OutputLimitExceeded)
+    /// );
+    /// ```
+    pub fn max_output_len(mut self, len: usize) -> Self {
+        self.max_output_len = Some(len);
+        self
+    }
+
+    /// Whether characters beyond those that JSON requires are escaped
+    pub(crate) fn escapes_more(&self) -> bool {
+        self.escape_non_ascii || self.escape_html
+    }
+
+    /// Whether `c` is escaped as `\uXXXX` because of these options
+    pub(crate) fn escapes_char(&self, c: char) -> bool {
+        (self.escape_non_ascii && !c.is_ascii())
+            || (self.escape_html && matches!(c, '<' | '>' | '&' | '\u{2028}' | '\u{2029}'))
+    }
+}
+
+/// Representation of unit enum variants like `Msg::Pause`
+///
+/// The deserializer accepts all of them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnitVariantStyle {
+    /// A string, i.e. `"pause"`
+    String,
+    /// An object with an empty object as its value, i.e. `{"pause":{}}`
+    EmptyObject,
+    /// An object with `null` as its value, i.e. `{"pause":null}`
+    Null,
+}
+
+// `#[default]` on enum variants requires Rust 1.62
+#[allow(clippy::derivable_impls)]
+impl Default for UnitVariantStyle {
+    fn default() -> Self {
+        UnitVariantStyle::String
+    }
+}
+
+/// Serializes to `{}`, the value of unit variants written as [`UnitVariantStyle::EmptyObject`]
+pub(crate) struct EmptyObject;
+
+impl Serialize for EmptyObject {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_struct("", 0)?.end()
+    }
+}
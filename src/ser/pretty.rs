@@ -0,0 +1,1077 @@
+//! Serialize a Rust data structure into pretty-printed JSON data
+
+use std::ops::Range;
+
+use serde::ser;
+
+use std::vec::Vec;
+
+use crate::ser::{to_vec_with_formatter, Error, Formatter, Options, Result};
+
+/// Layout of the output of [`to_vec_pretty_with_config`]
+///
+/// The default configuration produces the same output as [`to_vec_pretty`] with an indent of two
+/// spaces.
+///
+/// ```
+/// use serde_json_wasm::ser::{to_string_pretty_with_config, Options, PrettyConfig};
+///
+/// let config = PrettyConfig::new()
+///     .indent(b"    ")
+///     .space_after_colon(true)
+///     .inline_arrays(40)
+///     .trailing_newline(true);
+/// let json = to_string_pretty_with_config(&[("a", [1, 2])], &config, &Options::default());
+/// assert_eq!(json.unwrap(), "[\n    [\n        \"a\",\n        [1, 2]\n    ]\n]\n");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrettyConfig {
+    indent: Vec<u8>,
+    newline: Newline,
+    space_after_colon: bool,
+    inline_arrays: Option<usize>,
+    trailing_newline: bool,
+    sort_keys: bool,
+    sort_struct_fields: bool,
+}
+
+impl PrettyConfig {
+    /// Creates the default configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the bytes written once per indentation level. They must be JSON whitespace, i.e.
+    /// spaces, tabs, line feeds and carriage returns.
+    pub fn indent(mut self, indent: &[u8]) -> Self {
+        self.indent = indent.to_vec();
+        self
+    }
+
+    /// Sets the line ending
+    pub fn newline(mut self, newline: Newline) -> Self {
+        self.newline = newline;
+        self
+    }
+
+    /// Sets whether a space is written after the colon of object members
+    pub fn space_after_colon(mut self, enabled: bool) -> Self {
+        self.space_after_colon = enabled;
+        self
+    }
+
+    /// Keeps arrays that only contain strings, numbers, booleans and nulls on one line, like
+    /// `[1, 2, 3]`, if that line takes at most `max_width` bytes up to the closing bracket,
+    /// including the indentation and the key in front of the array
+    pub fn inline_arrays(mut self, max_width: usize) -> Self {
+        self.inline_arrays = Some(max_width);
+        self
+    }
+
+    /// Sets whether the output ends with a line ending
+    pub fn trailing_newline(mut self, enabled: bool) -> Self {
+        self.trailing_newline = enabled;
+        self
+    }
+
+    /// Sets whether the entries of maps are sorted by key, so that maps without a fixed
+    /// iteration order like `HashMap` always produce the same output.
+    ///
+    /// Keys are compared by their escaped text. This is meant for display and snapshots, the
+    /// output is not canonical JSON.
+    pub fn sort_keys(mut self, enabled: bool) -> Self {
+        self.sort_keys = enabled;
+        self
+    }
+
+    /// Sets whether the fields of structs are sorted by name instead of being written in
+    /// declaration order
+    pub fn sort_struct_fields(mut self, enabled: bool) -> Self {
+        self.sort_struct_fields = enabled;
+        self
+    }
+}
+
+impl Default for PrettyConfig {
+    fn default() -> Self {
+        PrettyConfig {
+            indent: b"  ".to_vec(),
+            newline: Newline::default(),
+            space_after_colon: false,
+            inline_arrays: None,
+            trailing_newline: false,
+            sort_keys: false,
+            sort_struct_fields: false,
+        }
+    }
+}
+
+/// Line ending written by the pretty-printer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Newline {
+    /// `\n`
+    Lf,
+    /// `\r\n`
+    CrLf,
+}
+
+impl Newline {
+    fn as_bytes(self) -> &'static [u8] {
+        match self {
+            Newline::Lf => b"\n",
+            Newline::CrLf => b"\r\n",
+        }
+    }
+}
+
+// `#[default]` on enum variants requires Rust 1.62
+#[allow(clippy::derivable_impls)]
+impl Default for Newline {
+    fn default() -> Self {
+        Newline::Lf
+    }
+}
+
+/// Formatter that puts every array element and object member on its own line
+///
+/// Nested values are indented by one more level. Empty arrays and objects are written as `[]` and
+/// `{}`.
+#[derive(Clone, Debug)]
+pub struct PrettyFormatter<'a> {
+    current_indent: usize,
+    has_value: bool,
+    indent: &'a [u8],
+    newline: &'static [u8],
+    space_after_colon: bool,
+    inline_arrays: Option<usize>,
+    /// The arrays that are currently open, if `inline_arrays` is set
+    arrays: Vec<OpenArray>,
+    sort_keys: bool,
+    sort_struct_fields: bool,
+    /// The objects that are currently open, if `sort_keys` or `sort_struct_fields` is set
+    objects: Vec<OpenObject>,
+}
+
+/// Returns how many bytes of the line `start` is on come before it, i.e. the indentation and the
+/// key of the member or the bracket of the array it is in
+fn line_prefix_len(writer: &[u8], start: usize) -> usize {
+    let line_start = writer[..start]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    start - line_start
+}
+
+/// Where an array and its elements were written, to put it on one line when it is closed
+#[derive(Clone, Debug)]
+struct OpenArray {
+    start: usize,
+    /// Length of the line in front of the array, see [`line_prefix_len`]
+    prefix: usize,
+    /// Length of the array on one line up to the last element, i.e. the opening bracket and the
+    /// elements before the last one with their separators
+    len: usize,
+    elements: Vec<Range<usize>>,
+    /// Whether the array only contains scalars and fits into the width so far, so that it can
+    /// still be put on one line
+    inlinable: bool,
+}
+
+/// Where the members of an object were written, to sort them when it is closed
+#[derive(Clone, Debug)]
+struct OpenObject {
+    sorted: bool,
+    members: Vec<Member>,
+}
+
+#[derive(Clone, Debug)]
+struct Member {
+    /// The key, including the quotes
+    key: Range<usize>,
+    /// The key, colon and value
+    text: Range<usize>,
+}
+
+impl<'a> PrettyFormatter<'a> {
+    /// Creates a formatter that indents with two spaces
+    pub fn new() -> Self {
+        PrettyFormatter::with_indent(b"  ")
+    }
+
+    /// Creates a formatter that writes `indent` once per indentation level
+    pub fn with_indent(indent: &'a [u8]) -> Self {
+        PrettyFormatter {
+            current_indent: 0,
+            has_value: false,
+            indent,
+            newline: Newline::Lf.as_bytes(),
+            space_after_colon: false,
+            inline_arrays: None,
+            arrays: Vec::new(),
+            sort_keys: false,
+            sort_struct_fields: false,
+            objects: Vec::new(),
+        }
+    }
+
+    /// Creates a formatter that lays out the output as described by `config`
+    pub fn with_config(config: &'a PrettyConfig) -> Self {
+        PrettyFormatter {
+            newline: config.newline.as_bytes(),
+            space_after_colon: config.space_after_colon,
+            inline_arrays: config.inline_arrays,
+            sort_keys: config.sort_keys,
+            sort_struct_fields: config.sort_struct_fields,
+            ..PrettyFormatter::with_indent(&config.indent)
+        }
+    }
+
+    fn write_indent(&self, writer: &mut Vec<u8>) {
+        for _ in 0..self.current_indent {
+            writer.extend_from_slice(self.indent);
+        }
+    }
+
+    /// Starts a new line for an element or member, after the comma if there is a previous one
+    fn begin_line(&self, writer: &mut Vec<u8>, first: bool) {
+        if !first {
+            writer.push(b',');
+        }
+        writer.extend_from_slice(self.newline);
+        self.write_indent(writer);
+    }
+
+    /// Puts the closing bracket or brace on its own line, unless the container is empty
+    fn end_container(&mut self, writer: &mut Vec<u8>, bracket: u8) {
+        self.current_indent -= 1;
+        if self.has_value {
+            writer.extend_from_slice(self.newline);
+            self.write_indent(writer);
+        }
+        writer.push(bracket);
+    }
+
+    /// Marks the innermost open array as containing an array or object
+    fn begin_container(&mut self) {
+        if let Some(parent) = self.arrays.last_mut() {
+            parent.inlinable = false;
+        }
+    }
+
+    fn open_object(&mut self, writer: &mut Vec<u8>, sorted: bool) {
+        self.begin_container();
+        if self.sort_keys || self.sort_struct_fields {
+            self.objects.push(OpenObject {
+                sorted,
+                members: Vec::new(),
+            });
+        }
+        self.current_indent += 1;
+        self.has_value = false;
+        writer.push(b'{');
+    }
+
+    /// Rewrites the members of the innermost object in the order of their keys, if it is sorted
+    fn sort_members(&mut self, writer: &mut Vec<u8>) {
+        let object = match self.objects.pop() {
+            Some(object) if object.sorted && object.members.len() > 1 => object,
+            _ => return,
+        };
+        // Without the quotes, so that `"a"` comes before `"a b"`
+        let key = |member: &Member| &writer[member.key.start + 1..member.key.end - 1];
+        let mut members: Vec<&Member> = object.members.iter().collect();
+        members.sort_by(|a, b| key(a).cmp(key(b)));
+
+        let mut separator = vec![b','];
+        separator.extend_from_slice(self.newline);
+        self.write_indent(&mut separator);
+        let mut sorted = Vec::new();
+        for (i, member) in members.iter().enumerate() {
+            if i > 0 {
+                sorted.extend_from_slice(&separator);
+            }
+            sorted.extend_from_slice(&writer[member.text.clone()]);
+        }
+        writer.truncate(object.members[0].text.start);
+        writer.extend_from_slice(&sorted);
+    }
+}
+
+impl Default for PrettyFormatter<'_> {
+    fn default() -> Self {
+        PrettyFormatter::new()
+    }
+}
+
+impl Formatter for PrettyFormatter<'_> {
+    fn begin_array(&mut self, writer: &mut Vec<u8>) {
+        if self.inline_arrays.is_some() {
+            self.begin_container();
+            self.arrays.push(OpenArray {
+                start: writer.len(),
+                prefix: line_prefix_len(writer, writer.len()),
+                len: 1,
+                elements: Vec::new(),
+                inlinable: true,
+            });
+        }
+        self.current_indent += 1;
+        self.has_value = false;
+        writer.push(b'[');
+    }
+
+    fn end_array(&mut self, writer: &mut Vec<u8>) {
+        self.end_container(writer, b']');
+
+        let (max_width, array) = match (self.inline_arrays, self.arrays.pop()) {
+            (Some(max_width), Some(array)) => (max_width, array),
+            _ => return,
+        };
+        if !array.inlinable || array.elements.is_empty() {
+            return;
+        }
+        // Neither scalars nor the raw fragments left in a scalars-only array contain a line
+        // break, so the elements can be copied as they are
+        let mut inline = vec![b'['];
+        for (i, element) in array.elements.iter().enumerate() {
+            if i > 0 {
+                inline.extend_from_slice(b", ");
+            }
+            inline.extend_from_slice(&writer[element.clone()]);
+        }
+        inline.push(b']');
+        if array.prefix + inline.len() <= max_width {
+            writer.truncate(array.start);
+            writer.extend_from_slice(&inline);
+        }
+    }
+
+    fn begin_array_value(&mut self, writer: &mut Vec<u8>, first: bool) {
+        self.begin_line(writer, first);
+        match self.arrays.last_mut() {
+            Some(array) if array.inlinable => {
+                if let Some(previous) = array.elements.last() {
+                    array.len += previous.len() + 2;
+                }
+                array.elements.push(writer.len()..writer.len());
+            }
+            _ => {}
+        }
+    }
+
+    fn end_array_value(&mut self, writer: &mut Vec<u8>) {
+        self.has_value = true;
+        let max_width = self.inline_arrays.unwrap_or(usize::MAX);
+        let array = match self.arrays.last_mut() {
+            Some(array) if array.inlinable => array,
+            _ => return,
+        };
+        if let Some(element) = array.elements.last_mut() {
+            element.end = writer.len();
+            // An array that got too wide can never be put on one line again
+            if array.prefix + array.len + element.len() + 1 > max_width {
+                array.inlinable = false;
+                array.elements = Vec::new();
+            }
+        }
+    }
+
+    fn reclaimable_len(&self, writer: &[u8]) -> usize {
+        // Only the innermost open array can still be put on one line, as any array or object in
+        // it makes the ones around it multi-line
+        let (max_width, array) = match (self.inline_arrays, self.arrays.last()) {
+            (Some(max_width), Some(array)) if array.inlinable => (max_width, array),
+            _ => return 0,
+        };
+        // The last element ends at the end of the output, even while it is still being written
+        let inline = match array.elements.last() {
+            Some(last) => array.len + writer.len() - last.start,
+            None => array.len,
+        };
+        // The closing bracket still follows
+        if array.prefix + inline + 1 > max_width {
+            return 0;
+        }
+        (writer.len() - array.start).saturating_sub(inline)
+    }
+
+    fn write_raw_fragment(&mut self, writer: &mut Vec<u8>, fragment: &str) {
+        // Raw arrays and objects, and anything spanning several lines, can't be put on one line
+        let bytes = fragment.as_bytes();
+        let multi_line = bytes.iter().any(|&b| b == b'\n' || b == b'\r');
+        if multi_line || matches!(fragment.trim_start().as_bytes().first(), Some(b'[' | b'{')) {
+            self.begin_container();
+        }
+        writer.extend_from_slice(bytes);
+    }
+
+    fn begin_object(&mut self, writer: &mut Vec<u8>) {
+        let sorted = self.sort_keys;
+        self.open_object(writer, sorted);
+    }
+
+    fn begin_struct(&mut self, writer: &mut Vec<u8>) {
+        let sorted = self.sort_struct_fields;
+        self.open_object(writer, sorted);
+    }
+
+    fn end_object(&mut self, writer: &mut Vec<u8>) {
+        self.sort_members(writer);
+        self.end_container(writer, b'}');
+    }
+
+    fn begin_object_key(&mut self, writer: &mut Vec<u8>, first: bool) {
+        self.begin_line(writer, first);
+        if let Some(object) = self.objects.last_mut() {
+            let start = writer.len();
+            object.members.push(Member {
+                key: start..start,
+                text: start..start,
+            });
+        }
+    }
+
+    fn end_object_key(&mut self, writer: &mut Vec<u8>) {
+        if let Some(member) = self.objects.last_mut().and_then(|o| o.members.last_mut()) {
+            member.key.end = writer.len();
+        }
+    }
+
+    fn begin_object_value(&mut self, writer: &mut Vec<u8>) {
+        if self.space_after_colon {
+            writer.extend_from_slice(b": ");
+        } else {
+            writer.push(b':');
+        }
+    }
+
+    fn end_object_value(&mut self, writer: &mut Vec<u8>) {
+        self.has_value = true;
+        if let Some(member) = self.objects.last_mut().and_then(|o| o.members.last_mut()) {
+            member.text.end = writer.len();
+        }
+    }
+}
+
+/// Fails with [`Error::InvalidIndent`] unless `indent` is JSON whitespace, which also makes sure
+/// that the output is valid UTF-8
+fn check_indent(indent: &[u8]) -> Result<()> {
+    if indent
+        .iter()
+        .all(|c| matches!(c, b' ' | b'\t' | b'\n' | b'\r'))
+    {
+        Ok(())
+    } else {
+        Err(Error::InvalidIndent)
+    }
+}
+
+/// Serializes the given data structure as a pretty-printed string of JSON text
+///
+/// Fails if `indent` is not JSON whitespace.
+pub fn to_string_pretty<T>(value: &T, indent: &[u8]) -> Result<String>
+where
+    T: ser::Serialize + ?Sized,
+{
+    to_string_pretty_with_options(value, indent, &Options::default())
+}
+
+/// Serializes the given data structure as a pretty-printed JSON byte vector
+///
+/// Fails if `indent` is not JSON whitespace.
+pub fn to_vec_pretty<T>(value: &T, indent: &[u8]) -> Result<Vec<u8>>
+where
+    T: ser::Serialize + ?Sized,
+{
+    to_vec_pretty_with_options(value, indent, &Options::default())
+}
+
+/// Serializes the given data structure as a pretty-printed string of JSON text, using the given
+/// options
+///
+/// Fails if `indent` is not JSON whitespace.
+pub fn to_string_pretty_with_options<T>(
+    value: &T,
+    indent: &[u8],
+    options: &Options,
+) -> Result<String>
+where
+    T: ser::Serialize + ?Sized,
+{
+    let buf = to_vec_pretty_with_options(value, indent, options)?;
+    Ok(unsafe { String::from_utf8_unchecked(buf) })
+}
+
+/// Serializes the given data structure as a pretty-printed JSON byte vector, using the given
+/// options
+///
+/// Fails if `indent` is not JSON whitespace.
+pub fn to_vec_pretty_with_options<T>(value: &T, indent: &[u8], options: &Options) -> Result<Vec<u8>>
+where
+    T: ser::Serialize + ?Sized,
+{
+    check_indent(indent)?;
+    to_vec_with_formatter(value, PrettyFormatter::with_indent(indent), options)
+}
+
+/// Serializes the given data structure as a pretty-printed string of JSON text, laid out as
+/// described by `config`
+///
+/// Fails if the indent of `config` is not JSON whitespace.
+pub fn to_string_pretty_with_config<T>(
+    value: &T,
+    config: &PrettyConfig,
+    options: &Options,
+) -> Result<String>
+where
+    T: ser::Serialize + ?Sized,
+{
+    let buf = to_vec_pretty_with_config(value, config, options)?;
+    Ok(unsafe { String::from_utf8_unchecked(buf) })
+}
+
+/// Serializes the given data structure as a pretty-printed JSON byte vector, laid out as described
+/// by `config`
+///
+/// Fails if the indent of `config` is not JSON whitespace.
+pub fn to_vec_pretty_with_config<T>(
+    value: &T,
+    config: &PrettyConfig,
+    options: &Options,
+) -> Result<Vec<u8>>
+where
+    T: ser::Serialize + ?Sized,
+{
+    check_indent(&config.indent)?;
+    let mut buf = to_vec_with_formatter(value, PrettyFormatter::with_config(config), options)?;
+    if config.trailing_newline {
+        buf.extend_from_slice(config.newline.as_bytes());
+        if matches!(options.max_output_len, Some(max) if buf.len() > max) {
+            return Err(Error::OutputLimitExceeded);
+        }
+    }
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::to_string_pretty;
+    use serde_derive::Serialize;
+
+    const INDENT: &[u8] = b"  ";
+
+    #[test]
+    fn enum_() {
+        #[derive(Serialize)]
+        enum Type {
+            #[serde(rename = "boolean")]
+            Boolean,
+            #[serde(rename = "number")]
+            Number,
+        }
+
+        assert_eq!(
+            to_string_pretty(&Type::Boolean, INDENT).unwrap(),
+            r#""boolean""#
+        );
+
+        assert_eq!(
+            to_string_pretty(&Type::Number, INDENT).unwrap(),
+            r#""number""#
+        );
+    }
+
+    #[test]
+    fn enum_with_options() {
+        use super::to_string_pretty_with_options;
+        use crate::ser::{Options, UnitVariantStyle};
+
+        #[derive(Serialize)]
+        enum Type {
+            #[serde(rename = "boolean")]
+            Boolean,
+        }
+
+        let options = Options::new().unit_variant_style(UnitVariantStyle::EmptyObject);
+        assert_eq!(
+            to_string_pretty_with_options(&[Type::Boolean], INDENT, &options).unwrap(),
+            "[\n  {\n    \"boolean\":{}\n  }\n]"
+        );
+
+        let options = Options::new().unit_variant_style(UnitVariantStyle::Null);
+        assert_eq!(
+            to_string_pretty_with_options(&Type::Boolean, INDENT, &options).unwrap(),
+            "{\n  \"boolean\":null\n}"
+        );
+    }
+
+    #[test]
+    fn str() {
+        assert_eq!(to_string_pretty("hello", INDENT).unwrap(), r#""hello""#);
+        assert_eq!(to_string_pretty("", INDENT).unwrap(), r#""""#);
+
+        // Characters unescaped if possible
+        assert_eq!(to_string_pretty("ä", INDENT).unwrap(), r#""ä""#);
+        assert_eq!(to_string_pretty("৬", INDENT).unwrap(), r#""৬""#);
+        // assert_eq!(to_string_pretty("\u{A0}").unwrap(), r#"" ""#); // non-breaking space
+        assert_eq!(to_string_pretty("ℝ", INDENT).unwrap(), r#""ℝ""#); // 3 byte character
+        assert_eq!(to_string_pretty("💣", INDENT).unwrap(), r#""💣""#); // 4 byte character
+
+        // " and \ must be escaped
+        assert_eq!(
+            to_string_pretty("foo\"bar", INDENT).unwrap(),
+            r#""foo\"bar""#
+        );
+        assert_eq!(
+            to_string_pretty("foo\\bar", INDENT).unwrap(),
+            r#""foo\\bar""#
+        );
+
+        // \b, \t, \n, \f, \r must be escaped in their two-character escaping
+        assert_eq!(to_string_pretty(" \u{0008} ", INDENT).unwrap(), r#"" \b ""#);
+        assert_eq!(to_string_pretty(" \u{0009} ", INDENT).unwrap(), r#"" \t ""#);
+        assert_eq!(to_string_pretty(" \u{000A} ", INDENT).unwrap(), r#"" \n ""#);
+        assert_eq!(to_string_pretty(" \u{000C} ", INDENT).unwrap(), r#"" \f ""#);
+        assert_eq!(to_string_pretty(" \u{000D} ", INDENT).unwrap(), r#"" \r ""#);
+
+        // U+0000 through U+001F is escaped using six-character \u00xx uppercase hexadecimal escape sequences
+        assert_eq!(
+            to_string_pretty(" \u{0000} ", INDENT).unwrap(),
+            r#"" \u0000 ""#
+        );
+        assert_eq!(
+            to_string_pretty(" \u{0001} ", INDENT).unwrap(),
+            r#"" \u0001 ""#
+        );
+        assert_eq!(
+            to_string_pretty(" \u{0007} ", INDENT).unwrap(),
+            r#"" \u0007 ""#
+        );
+        assert_eq!(
+            to_string_pretty(" \u{000e} ", INDENT).unwrap(),
+            r#"" \u000E ""#
+        );
+        assert_eq!(
+            to_string_pretty(" \u{001D} ", INDENT).unwrap(),
+            r#"" \u001D ""#
+        );
+        assert_eq!(
+            to_string_pretty(" \u{001f} ", INDENT).unwrap(),
+            r#"" \u001F ""#
+        );
+    }
+
+    #[test]
+    fn struct_bool() {
+        #[derive(Serialize)]
+        struct Led {
+            led: bool,
+        }
+
+        assert_eq!(
+            to_string_pretty(&Led { led: true }, INDENT).unwrap(),
+            r#"{
+  "led":true
+}"#
+        );
+    }
+
+    #[test]
+    fn struct_i8() {
+        #[derive(Serialize)]
+        struct Temperature {
+            temperature: i8,
+        }
+
+        assert_eq!(
+            to_string_pretty(&Temperature { temperature: 127 }, INDENT).unwrap(),
+            r#"{
+  "temperature":127
+}"#
+        );
+
+        assert_eq!(
+            to_string_pretty(&Temperature { temperature: 20 }, INDENT).unwrap(),
+            r#"{
+  "temperature":20
+}"#
+        );
+
+        assert_eq!(
+            to_string_pretty(&Temperature { temperature: -17 }, INDENT).unwrap(),
+            r#"{
+  "temperature":-17
+}"#
+        );
+
+        assert_eq!(
+            to_string_pretty(&Temperature { temperature: -128 }, INDENT).unwrap(),
+            r#"{
+  "temperature":-128
+}"#
+        );
+    }
+
+    #[test]
+    fn struct_option() {
+        use super::to_string_pretty_with_options;
+        use crate::ser::Options;
+
+        #[derive(Serialize)]
+        struct Property<'a> {
+            description: Option<&'a str>,
+        }
+
+        assert_eq!(
+            to_string_pretty(
+                &Property {
+                    description: Some("An ambient temperature sensor"),
+                },
+                INDENT
+            )
+            .unwrap(),
+            r#"{
+  "description":"An ambient temperature sensor"
+}"#
+        );
+
+        // `None` is written as `null` unless `skip_none_fields` is set
+        assert_eq!(
+            to_string_pretty(&Property { description: None }, INDENT).unwrap(),
+            r#"{
+  "description":null
+}"#
+        );
+
+        let options = Options::new().skip_none_fields(true);
+        assert_eq!(
+            to_string_pretty_with_options(&Property { description: None }, INDENT, &options)
+                .unwrap(),
+            "{}"
+        );
+        assert_eq!(
+            to_string_pretty_with_options(&[Property { description: None }], INDENT, &options)
+                .unwrap(),
+            "[\n  {}\n]"
+        );
+    }
+
+    #[test]
+    fn struct_u8() {
+        #[derive(Serialize)]
+        struct Temperature {
+            temperature: u8,
+        }
+
+        assert_eq!(
+            to_string_pretty(&Temperature { temperature: 20 }, INDENT).unwrap(),
+            r#"{
+  "temperature":20
+}"#
+        );
+    }
+
+    #[test]
+    fn struct_() {
+        #[derive(Serialize)]
+        struct Empty {}
+
+        assert_eq!(to_string_pretty(&Empty {}, INDENT).unwrap(), r#"{}"#);
+
+        #[derive(Serialize)]
+        struct Tuple {
+            a: bool,
+            b: bool,
+        }
+
+        assert_eq!(
+            to_string_pretty(&Tuple { a: true, b: false }, INDENT).unwrap(),
+            r#"{
+  "a":true,
+  "b":false
+}"#
+        );
+    }
+
+    #[test]
+    fn test_unit() {
+        let a = ();
+        assert_eq!(to_string_pretty(&a, INDENT).unwrap(), r#"null"#);
+    }
+
+    #[test]
+    fn test_newtype_struct() {
+        #[derive(Serialize)]
+        struct A(pub u32);
+        let a = A(54);
+        assert_eq!(to_string_pretty(&a, INDENT).unwrap(), r#"54"#);
+    }
+
+    #[test]
+    fn test_newtype_variant() {
+        #[derive(Serialize)]
+        enum A {
+            A(u32),
+        }
+        let a = A::A(54);
+
+        assert_eq!(
+            to_string_pretty(&a, INDENT).unwrap(),
+            r#"{
+  "A":54
+}"#
+        );
+    }
+
+    #[test]
+    fn test_struct_variant() {
+        #[derive(Serialize)]
+        enum A {
+            A { x: u32, y: u16 },
+        }
+        let a = A::A { x: 54, y: 720 };
+
+        assert_eq!(
+            to_string_pretty(&a, INDENT).unwrap(),
+            r#"{
+  "A":{
+    "x":54,
+    "y":720
+  }
+}"#
+        );
+    }
+
+    #[test]
+    fn test_tuple_variant() {
+        #[derive(Serialize)]
+        enum A {
+            A(u32, u16),
+        }
+        let a = A::A(54, 720);
+
+        assert_eq!(
+            to_string_pretty(&a, INDENT).unwrap(),
+            r#"{
+  "A":[
+    54,
+    720
+  ]
+}"#
+        );
+    }
+
+    #[test]
+    fn nested_and_empty() {
+        use std::collections::BTreeMap;
+
+        let mut map = BTreeMap::new();
+        map.insert("a", vec![vec![], vec![1u8]]);
+        map.insert("b", vec![]);
+
+        assert_eq!(
+            to_string_pretty(&map, b"\t").unwrap(),
+            "{\n\t\"a\":[\n\t\t[],\n\t\t[\n\t\t\t1\n\t\t]\n\t],\n\t\"b\":[]\n}"
+        );
+    }
+
+    #[test]
+    fn pretty_config() {
+        use super::{to_string_pretty_with_config, Newline, PrettyConfig};
+        use crate::ser::Options;
+        use std::collections::BTreeMap;
+
+        let mut map = BTreeMap::new();
+        map.insert("a", vec![vec![1u8, 2], vec![]]);
+        map.insert("b", vec![vec![3, 4, 5]]);
+        let options = Options::default();
+
+        assert_eq!(
+            to_string_pretty_with_config(&map, &PrettyConfig::new(), &options).unwrap(),
+            to_string_pretty(&map, INDENT).unwrap()
+        );
+
+        let config = PrettyConfig::new()
+            .indent(b"\t")
+            .newline(Newline::CrLf)
+            .space_after_colon(true)
+            .trailing_newline(true);
+        assert_eq!(
+            to_string_pretty_with_config(&map, &config, &options).unwrap(),
+            "{\r\n\t\"a\": [\r\n\t\t[\r\n\t\t\t1,\r\n\t\t\t2\r\n\t\t],\r\n\t\t[]\r\n\t],\r\n\t\"b\": [\r\n\t\t[\r\n\t\t\t3,\r\n\t\t\t4,\r\n\t\t\t5\r\n\t\t]\r\n\t]\r\n}\r\n"
+        );
+
+        // With their indentation, [1, 2] takes 10 bytes and [3, 4, 5] takes 13
+        let config = PrettyConfig::new().inline_arrays(12);
+        assert_eq!(
+            to_string_pretty_with_config(&map, &config, &options).unwrap(),
+            "{\n  \"a\":[\n    [1, 2],\n    []\n  ],\n  \"b\":[\n    [\n      3,\n      4,\n      5\n    ]\n  ]\n}"
+        );
+        let config = PrettyConfig::new().inline_arrays(11);
+        assert_eq!(
+            to_string_pretty_with_config(&map["b"], &config, &options).unwrap(),
+            "[\n  [3, 4, 5]\n]"
+        );
+        let config = PrettyConfig::new().inline_arrays(10);
+        assert_eq!(
+            to_string_pretty_with_config(&map["b"], &config, &options).unwrap(),
+            "[\n  [\n    3,\n    4,\n    5\n  ]\n]"
+        );
+        // The key in front of the array counts as well
+        let flat: BTreeMap<_, _> = vec![("b", [3, 4, 5])].into_iter().collect();
+        let config = PrettyConfig::new().inline_arrays(15);
+        assert_eq!(
+            to_string_pretty_with_config(&flat, &config, &options).unwrap(),
+            "{\n  \"b\":[3, 4, 5]\n}"
+        );
+        let config = PrettyConfig::new().inline_arrays(14);
+        assert_eq!(
+            to_string_pretty_with_config(&flat, &config, &options).unwrap(),
+            "{\n  \"b\":[\n    3,\n    4,\n    5\n  ]\n}"
+        );
+        let config = PrettyConfig::new().inline_arrays(16);
+        assert_eq!(
+            to_string_pretty_with_config(&["x\ny", "z"], &config, &options).unwrap(),
+            r#"["x\ny", "z"]"#
+        );
+    }
+
+    #[test]
+    fn reclaimable_len() {
+        use super::{PrettyConfig, PrettyFormatter};
+        use crate::ser::Formatter;
+
+        let config = PrettyConfig::new().inline_arrays(12);
+        let mut formatter = PrettyFormatter::with_config(&config);
+        let mut writer = Vec::new();
+        formatter.begin_array(&mut writer);
+        for (i, element) in [&b"1"[..], b"22", b"333"].iter().enumerate() {
+            formatter.begin_array_value(&mut writer, i == 0);
+            writer.extend_from_slice(element);
+            formatter.end_array_value(&mut writer);
+        }
+        // "[\n  1,\n  22,\n  333" is 18 bytes, "[1, 22, 333" is 11
+        assert_eq!(formatter.reclaimable_len(&writer), 7);
+
+        // Once the array is wider than allowed, it stays multi-line
+        formatter.begin_array_value(&mut writer, false);
+        writer.extend_from_slice(b"4");
+        formatter.end_array_value(&mut writer);
+        assert_eq!(formatter.reclaimable_len(&writer), 0);
+        formatter.end_array(&mut writer);
+        assert_eq!(writer, b"[\n  1,\n  22,\n  333,\n  4\n]");
+    }
+
+    #[test]
+    fn pretty_config_keeps_raw_containers_on_their_lines() {
+        use super::{to_string_pretty_with_config, PrettyConfig};
+        use crate::ser::Options;
+        use crate::RawValue;
+
+        let config = PrettyConfig::new().inline_arrays(80);
+        let options = Options::default();
+        let raw = |json: &str| RawValue::from_string(json.to_string()).unwrap();
+
+        let values = vec![raw("1"), raw("\"a\"")];
+        assert_eq!(
+            to_string_pretty_with_config(&values, &config, &options).unwrap(),
+            r#"[1, "a"]"#
+        );
+        let values = vec![raw("1"), raw("[1,\n2]")];
+        assert_eq!(
+            to_string_pretty_with_config(&values, &config, &options).unwrap(),
+            "[\n  1,\n  [1,\n2]\n]"
+        );
+        let values = vec![raw("{\n}")];
+        assert_eq!(
+            to_string_pretty_with_config(&values, &config, &options).unwrap(),
+            "[\n  {\n}\n]"
+        );
+        let values = vec![raw(r#"{"a":1}"#)];
+        assert_eq!(
+            to_string_pretty_with_config(&values, &config, &options).unwrap(),
+            "[\n  {\"a\":1}\n]"
+        );
+    }
+
+    #[test]
+    fn pretty_config_rejects_invalid_indent() {
+        use super::{to_vec_pretty_with_config, PrettyConfig};
+        use crate::ser::{Error, Options};
+
+        let config = PrettyConfig::new().indent(b"--");
+        assert!(matches!(
+            to_vec_pretty_with_config(&[1], &config, &Options::default()),
+            Err(Error::InvalidIndent)
+        ));
+        let config = PrettyConfig::new().indent(b" \t\r\n");
+        assert!(to_vec_pretty_with_config(&[1], &config, &Options::default()).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_indent() {
+        use super::{to_string_pretty_with_options, to_vec_pretty};
+        use crate::ser::{Error, Options};
+
+        // Not valid UTF-8, which must not end up in a `String`
+        assert_eq!(to_string_pretty(&[1], b"\xff"), Err(Error::InvalidIndent));
+        assert_eq!(
+            to_string_pretty_with_options(&[1], b"--", &Options::default()),
+            Err(Error::InvalidIndent)
+        );
+        assert_eq!(to_vec_pretty(&[1], b"\t"), Ok(b"[\n\t1\n]".to_vec()));
+    }
+
+    #[test]
+    fn pretty_config_sorts_keys() {
+        use super::{to_string_pretty_with_config, PrettyConfig};
+        use crate::ser::Options;
+        use std::collections::HashMap;
+
+        #[derive(Serialize)]
+        struct Entry {
+            z: u8,
+            map: HashMap<&'static str, u8>,
+        }
+
+        let map: HashMap<_, _> = [("b", 2), ("a b", 3), ("a", 1), ("c\"", 4)]
+            .iter()
+            .cloned()
+            .collect();
+        let value = vec![Entry { z: 0, map }];
+        let options = Options::default();
+
+        let config = PrettyConfig::new().sort_keys(true).inline_arrays(80);
+        assert_eq!(
+            to_string_pretty_with_config(&value, &config, &options).unwrap(),
+            r#"[
+  {
+    "z":0,
+    "map":{
+      "a":1,
+      "a b":3,
+      "b":2,
+      "c\"":4
+    }
+  }
+]"#
+        );
+
+        let config = config.sort_struct_fields(true);
+        assert_eq!(
+            to_string_pretty_with_config(&value, &config, &options).unwrap(),
+            r#"[
+  {
+    "map":{
+      "a":1,
+      "a b":3,
+      "b":2,
+      "c\"":4
+    },
+    "z":0
+  }
+]"#
+        );
+    }
+}
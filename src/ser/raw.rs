@@ -0,0 +1,209 @@
+use std::fmt;
+
+use serde::{ser, Serialize};
+
+use crate::ser::{CharEscape, Error, Formatter, Options, Result, Unreachable};
+
+/// Serializer for the contents of a `RawValue`, which writes the JSON text it is given verbatim,
+/// apart from the characters that the options escape
+pub(crate) struct RawValueEmitter<'a, F> {
+    pub(crate) buf: &'a mut Vec<u8>,
+    pub(crate) formatter: &'a mut F,
+    pub(crate) options: &'a Options,
+}
+
+impl<'a, F> ser::Serializer for RawValueEmitter<'a, F>
+where
+    F: Formatter,
+{
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Unreachable;
+    type SerializeTuple = Unreachable;
+    type SerializeTupleStruct = Unreachable;
+    type SerializeTupleVariant = Unreachable;
+    type SerializeMap = Unreachable;
+    type SerializeStruct = Unreachable;
+    type SerializeStructVariant = Unreachable;
+
+    fn serialize_str(self, value: &str) -> Result<()> {
+        crate::de::check_value(value.as_bytes()).map_err(|_| Error::InvalidRawValue)?;
+        if !self.options.escapes_more() {
+            self.formatter.write_raw_fragment(self.buf, value);
+            return Ok(());
+        }
+
+        // Outside of strings, valid JSON text only contains ASCII characters other than `<`, `>`
+        // and `&`. So the characters to escape are all in strings, where an escape sequence means
+        // the same as the character itself.
+        let mut escaped = Vec::with_capacity(value.len());
+        let mut start = 0;
+        for (i, c) in value.char_indices() {
+            if !self.options.escapes_char(c) {
+                continue;
+            }
+            escaped.extend_from_slice(&value.as_bytes()[start..i]);
+            let mut units = [0u16; 2];
+            for &unit in c.encode_utf16(&mut units).iter() {
+                self.formatter
+                    .write_char_escape(&mut escaped, CharEscape::Unicode(unit));
+            }
+            start = i + c.len_utf8();
+        }
+        escaped.extend_from_slice(&value.as_bytes()[start..]);
+        let escaped = String::from_utf8(escaped).map_err(|_| Error::InvalidUtf8)?;
+        self.formatter.write_raw_fragment(self.buf, &escaped);
+        Ok(())
+    }
+
+    fn serialize_bool(self, _value: bool) -> Result<()> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_i8(self, _value: i8) -> Result<()> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_i16(self, _value: i16) -> Result<()> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_i32(self, _value: i32) -> Result<()> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_i64(self, _value: i64) -> Result<()> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_u8(self, _value: u8) -> Result<()> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_u16(self, _value: u16) -> Result<()> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_u32(self, _value: u32) -> Result<()> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_u64(self, _value: u64) -> Result<()> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_f32(self, _value: f32) -> Result<()> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_f64(self, _value: f64) -> Result<()> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_char(self, _value: char) -> Result<()> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_bytes(self, _value: &[u8]) -> Result<()> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<()> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, _value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(Error::InvalidRawValue)
+    }
+
+    fn collect_str<T>(self, _value: &T) -> Result<()>
+    where
+        T: ?Sized + fmt::Display,
+    {
+        Err(Error::InvalidRawValue)
+    }
+}
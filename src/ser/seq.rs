@@ -1,48 +1,58 @@
 use serde::ser;
 
-use crate::ser::{Error, Result, Serializer};
+use crate::ser::{Error, Formatter, Result, Serializer};
 
-pub struct SerializeSeq<'a> {
-    ser: &'a mut Serializer,
+pub struct SerializeSeq<'a, F> {
+    ser: &'a mut Serializer<F>,
     first: bool,
 }
 
-impl<'a> SerializeSeq<'a> {
-    pub(crate) fn new(ser: &'a mut Serializer) -> Self {
+impl<'a, F> SerializeSeq<'a, F>
+where
+    F: Formatter,
+{
+    pub(crate) fn new(ser: &'a mut Serializer<F>) -> Self {
         SerializeSeq { ser, first: true }
     }
 }
 
-impl<'a> ser::SerializeSeq for SerializeSeq<'a> {
+impl<'a, F> ser::SerializeSeq for SerializeSeq<'a, F>
+where
+    F: Formatter,
+{
     type Ok = ();
     type Error = Error;
 
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<()>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
     where
-        T: ser::Serialize,
+        T: ?Sized + ser::Serialize,
     {
-        if !self.first {
-            self.ser.buf.push(b',');
-        }
+        self.ser
+            .formatter
+            .begin_array_value(&mut self.ser.buf, self.first);
         self.first = false;
 
         value.serialize(&mut *self.ser)?;
+        self.ser.formatter.end_array_value(&mut self.ser.buf);
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok> {
-        self.ser.buf.push(b']');
-        Ok(())
+        self.ser.formatter.end_array(&mut self.ser.buf);
+        self.ser.check_len()
     }
 }
 
-impl<'a> ser::SerializeTuple for SerializeSeq<'a> {
+impl<'a, F> ser::SerializeTuple for SerializeSeq<'a, F>
+where
+    F: Formatter,
+{
     type Ok = ();
     type Error = Error;
 
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<()>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
     where
-        T: ser::Serialize,
+        T: ?Sized + ser::Serialize,
     {
         ser::SerializeSeq::serialize_element(self, value)
     }
@@ -52,22 +62,24 @@ impl<'a> ser::SerializeTuple for SerializeSeq<'a> {
     }
 }
 
-impl<'a> ser::SerializeTupleVariant for SerializeSeq<'a> {
+impl<'a, F> ser::SerializeTupleVariant for SerializeSeq<'a, F>
+where
+    F: Formatter,
+{
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
     where
-        T: ser::Serialize,
+        T: ?Sized + ser::Serialize,
     {
         ser::SerializeSeq::serialize_element(self, value)
     }
 
     fn end(self) -> Result<Self::Ok> {
         // close sequence
-        self.ser.buf.push(b']');
+        self.ser.formatter.end_array(&mut self.ser.buf);
         // close surrounding enum
-        self.ser.buf.push(b'}');
-        Ok(())
+        self.ser.end_variant()
     }
 }
@@ -1,75 +1,90 @@
 use serde::ser;
 
-use crate::ser::{Error, Result, Serializer};
+use crate::ser::none::is_none;
+use crate::ser::{Error, Formatter, Result, Serializer};
 
-pub struct SerializeStruct<'a> {
-    ser: &'a mut Serializer,
+/// Returns true if `key` is printable ASCII that is never escaped, which is the case for almost all
+/// field names
+fn is_plain(key: &str, escape_html: bool) -> bool {
+    key.bytes().all(|byte| match byte {
+        b'"' | b'\\' => false,
+        b'<' | b'>' | b'&' => !escape_html,
+        _ => (0x20..0x7F).contains(&byte),
+    })
+}
+
+pub struct SerializeStruct<'a, F> {
+    ser: &'a mut Serializer<F>,
     first: bool,
 }
 
-impl<'a> SerializeStruct<'a> {
-    pub(crate) fn new(ser: &'a mut Serializer) -> Self {
+impl<'a, F> SerializeStruct<'a, F>
+where
+    F: Formatter,
+{
+    pub(crate) fn new(ser: &'a mut Serializer<F>) -> Self {
         SerializeStruct { ser, first: true }
     }
 }
 
-impl<'a> ser::SerializeStruct for SerializeStruct<'a> {
+impl<'a, F> ser::SerializeStruct for SerializeStruct<'a, F>
+where
+    F: Formatter,
+{
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(&mut self, key: &'static str, value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
     where
-        T: ser::Serialize,
+        T: ?Sized + ser::Serialize,
     {
-        // XXX if `value` is `None` we not produce any output for this field
-        if !self.first {
-            self.ser.buf.push(b',');
+        let ser = &mut *self.ser;
+        if ser.options.skip_none_fields && is_none(value) {
+            return Ok(());
         }
+        ser.formatter.begin_object_key(&mut ser.buf, self.first);
         self.first = false;
 
-        self.ser.buf.push(b'"');
-        self.ser.buf.extend_from_slice(key.as_bytes());
-        self.ser.buf.extend_from_slice(b"\":");
+        if is_plain(key, ser.options.escape_html) {
+            ser.formatter.begin_string(&mut ser.buf);
+            ser.formatter.write_string_fragment(&mut ser.buf, key);
+            ser.formatter.end_string(&mut ser.buf);
+        } else {
+            ser.write_str(key)?;
+        }
+        ser.formatter.end_object_key(&mut ser.buf);
 
-        value.serialize(&mut *self.ser)?;
+        ser.formatter.begin_object_value(&mut ser.buf);
+        value.serialize(&mut *ser)?;
+        ser.formatter.end_object_value(&mut ser.buf);
 
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok> {
-        self.ser.buf.push(b'}');
-        Ok(())
+        self.ser.formatter.end_object(&mut self.ser.buf);
+        self.ser.check_len()
     }
 }
 
-impl<'a> ser::SerializeStructVariant for SerializeStruct<'a> {
+impl<'a, F> ser::SerializeStructVariant for SerializeStruct<'a, F>
+where
+    F: Formatter,
+{
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(&mut self, key: &'static str, value: &T) -> Result<()>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
     where
-        T: ser::Serialize,
+        T: ?Sized + ser::Serialize,
     {
-        // XXX if `value` is `None` we not produce any output for this field
-        if !self.first {
-            self.ser.buf.push(b',');
-        }
-        self.first = false;
-
-        self.ser.buf.push(b'"');
-        self.ser.buf.extend_from_slice(key.as_bytes());
-        self.ser.buf.extend_from_slice(b"\":");
-
-        value.serialize(&mut *self.ser)?;
-
-        Ok(())
+        ser::SerializeStruct::serialize_field(self, key, value)
     }
 
     fn end(self) -> Result<Self::Ok> {
         // close struct
-        self.ser.buf.push(b'}');
+        self.ser.formatter.end_object(&mut self.ser.buf);
         // close surrounding enum
-        self.ser.buf.push(b'}');
-        Ok(())
+        self.ser.end_variant()
     }
 }
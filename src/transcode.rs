@@ -0,0 +1,183 @@
+//! Reformatting of JSON text without a Rust type
+
+use crate::de::{Event, Result, Tokenizer};
+use crate::ser::{write_escaped, CompactFormatter, Formatter, Options, PrettyFormatter};
+
+/// Removes all insignificant whitespace from the JSON document in `v`.
+///
+/// The document is validated with the rules of the deserializer. Keys keep their order and
+/// integers their exact text, even if they do not fit into any Rust integer type. Strings are
+/// written the same way the serializer writes them, so escape sequences may change.
+///
+/// ```
+/// use serde_json_wasm::transcode::minify;
+///
+/// let json = b"{ \"b\": [1, 2],\n  \"a\": 123456789012345678901234567890 }";
+/// assert_eq!(
+///     minify(json).unwrap(),
+///     br#"{"b":[1,2],"a":123456789012345678901234567890}"#
+/// );
+/// ```
+pub fn minify(v: &[u8]) -> Result<Vec<u8>> {
+    transcode(v, CompactFormatter)
+}
+
+/// Reformats the JSON document in `v` like [`to_vec_pretty`](crate::to_vec_pretty) does.
+///
+/// See [`minify`] for how the document is treated.
+///
+/// ```
+/// use serde_json_wasm::transcode::prettify;
+///
+/// assert_eq!(
+///     prettify(br#"{"a":[1,2]}"#, b"  ").unwrap(),
+///     b"{\n  \"a\":[\n    1,\n    2\n  ]\n}"
+/// );
+/// ```
+pub fn prettify(v: &[u8], indent: &[u8]) -> Result<Vec<u8>> {
+    transcode(v, PrettyFormatter::with_indent(indent))
+}
+
+/// Writes the events of the document in `v` through `formatter`, calling its hooks in the same
+/// order as the serializer does
+fn transcode<F>(v: &[u8], mut formatter: F) -> Result<Vec<u8>>
+where
+    F: Formatter,
+{
+    let options = Options::default();
+    let mut buf = Vec::with_capacity(v.len());
+    // Whether each open container is an array
+    let mut open: Vec<bool> = Vec::new();
+    // Whether the next element or member is the first of the innermost container
+    let mut first = true;
+
+    for item in Tokenizer::new(v) {
+        let (event, _) = item?;
+        match event {
+            Event::Key(key) => {
+                formatter.begin_object_key(&mut buf, first);
+                first = false;
+                write_string(&mut buf, &mut formatter, &key, &options);
+                formatter.end_object_key(&mut buf);
+                formatter.begin_object_value(&mut buf);
+                continue;
+            }
+            Event::EndArray | Event::EndObject => {
+                if open.pop() == Some(true) {
+                    formatter.end_array(&mut buf);
+                } else {
+                    formatter.end_object(&mut buf);
+                }
+                first = false;
+            }
+            event => {
+                if open.last() == Some(&true) {
+                    formatter.begin_array_value(&mut buf, first);
+                    first = false;
+                }
+                match event {
+                    Event::StartArray | Event::StartObject => {
+                        let array = event == Event::StartArray;
+                        if array {
+                            formatter.begin_array(&mut buf);
+                        } else {
+                            formatter.begin_object(&mut buf);
+                        }
+                        open.push(array);
+                        first = true;
+                        continue;
+                    }
+                    Event::String(s) => write_string(&mut buf, &mut formatter, &s, &options),
+                    Event::Integer(digits) => formatter.write_integer(&mut buf, digits),
+                    Event::Bool(b) => formatter.write_bool(&mut buf, b),
+                    Event::Null => formatter.write_null(&mut buf),
+                    Event::Key(_) | Event::EndArray | Event::EndObject => unreachable!(),
+                }
+            }
+        }
+        // A complete value was written
+        match open.last() {
+            Some(true) => formatter.end_array_value(&mut buf),
+            Some(false) => formatter.end_object_value(&mut buf),
+            None => {}
+        }
+    }
+    Ok(buf)
+}
+
+fn write_string<F>(buf: &mut Vec<u8>, formatter: &mut F, s: &str, options: &Options)
+where
+    F: Formatter,
+{
+    formatter.begin_string(buf);
+    write_escaped(buf, formatter, s, options);
+    formatter.end_string(buf);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{minify, prettify};
+    use crate::de::Error;
+    use serde_derive::Serialize;
+
+    #[test]
+    fn minify_works() {
+        assert_eq!(minify(b" null ").unwrap(), b"null");
+        assert_eq!(
+            minify(b"{\n  \"z\" : [ 1 , -0 , true , false ] ,\n  \"a\" : { } , \"m\" : [ ] }\n")
+                .unwrap(),
+            br#"{"z":[1,-0,true,false],"a":{},"m":[]}"#
+        );
+        assert_eq!(
+            minify(br#"[-340282366920938463463374607431768211457]"#).unwrap(),
+            br#"[-340282366920938463463374607431768211457]"#
+        );
+        assert_eq!(
+            minify(br#"{"k\u0065y": "tab\tquote\"slash\/"}"#).unwrap(),
+            br#"{"key":"tab\tquote\"slash/"}"#
+        );
+    }
+
+    #[test]
+    fn prettify_works() {
+        assert_eq!(prettify(b"[]", b"  ").unwrap(), b"[]");
+        assert_eq!(prettify(b"{}", b"  ").unwrap(), b"{}");
+        assert_eq!(
+            prettify(br#"{"b":{"c":[1,{}]},"a":"x"}"#, b"\t").unwrap(),
+            b"{\n\t\"b\":{\n\t\t\"c\":[\n\t\t\t1,\n\t\t\t{}\n\t\t]\n\t},\n\t\"a\":\"x\"\n}"
+        );
+
+        // Same layout as the serializer
+        #[derive(Serialize)]
+        struct Doc {
+            list: (u8, [u8; 0]),
+            text: &'static str,
+            none: Option<u8>,
+        }
+        let doc = Doc {
+            list: (1, []),
+            text: "a\"b\u{e9}",
+            none: None,
+        };
+        assert_eq!(
+            prettify(br#"{"list":[1,[]],"text":"a\"b\u00e9","none":null}"#, b"  ").unwrap(),
+            crate::to_vec_pretty(&doc, b"  ").unwrap()
+        );
+
+        // Round trip
+        let pretty = prettify(br#"{"a":[1,2,{"b":null}],"c":"d"}"#, b"    ").unwrap();
+        assert_eq!(
+            minify(&pretty).unwrap(),
+            br#"{"a":[1,2,{"b":null}],"c":"d"}"#
+        );
+    }
+
+    #[test]
+    fn transcode_validates_input() {
+        assert_eq!(minify(b""), Err(Error::EofWhileParsingValue));
+        assert_eq!(minify(b"[1,]"), Err(Error::TrailingComma));
+        assert_eq!(minify(b"{\"a\":1.5}"), Err(Error::ExpectedObjectCommaOrEnd));
+        assert_eq!(minify(b"{} []"), Err(Error::TrailingCharacters));
+        assert_eq!(prettify(b"[\"\\q\"]", b"  "), Err(Error::InvalidEscape));
+    }
+}